@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS wordle_scores (
+    guild_id BIGINT NOT NULL REFERENCES guilds (guild_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+    guesses INTEGER NOT NULL,
+    hard_mode BOOLEAN NOT NULL,
+    board INTEGER NOT NULL,
+    day_added INTEGER NOT NULL,
+    UNIQUE (guild_id, user_id, board)
+);
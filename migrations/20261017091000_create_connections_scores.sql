@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS connections_scores (
+    guild_id BIGINT NOT NULL REFERENCES guilds (guild_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+    mistakes INTEGER NOT NULL,
+    solved BOOLEAN NOT NULL,
+    purple_first BOOLEAN NOT NULL,
+    board INTEGER NOT NULL,
+    day_added INTEGER NOT NULL,
+    UNIQUE (guild_id, user_id, board)
+);
@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS worldle_scores (
+    guild_id BIGINT NOT NULL REFERENCES guilds (guild_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+    guesses INTEGER NOT NULL,
+    board INTEGER NOT NULL,
+    day_added INTEGER NOT NULL,
+    UNIQUE (guild_id, user_id, board)
+);
+
+CREATE TABLE IF NOT EXISTS globle_scores (
+    guild_id BIGINT NOT NULL REFERENCES guilds (guild_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+    guesses INTEGER NOT NULL,
+    board INTEGER NOT NULL,
+    day_added INTEGER NOT NULL,
+    UNIQUE (guild_id, user_id, board)
+);
+
+CREATE TABLE IF NOT EXISTS travle_scores (
+    guild_id BIGINT NOT NULL REFERENCES guilds (guild_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+    guesses INTEGER NOT NULL,
+    extra_guesses INTEGER,
+    board INTEGER NOT NULL,
+    day_added INTEGER NOT NULL,
+    UNIQUE (guild_id, user_id, board)
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS guild_settings (
+    guild_id BIGINT PRIMARY KEY NOT NULL REFERENCES guilds (guild_id) ON DELETE CASCADE,
+    results_channel_id BIGINT
+);
+
+CREATE TABLE IF NOT EXISTS results_posts (
+    guild_id BIGINT NOT NULL REFERENCES guilds (guild_id) ON DELETE CASCADE,
+    game TEXT NOT NULL,
+    board INTEGER NOT NULL,
+    UNIQUE (guild_id, game, board)
+);
@@ -0,0 +1,14 @@
+ALTER TABLE guild_settings
+ADD COLUMN IF NOT EXISTS reactions_enabled BOOLEAN NOT NULL DEFAULT TRUE;
+
+CREATE TABLE IF NOT EXISTS guild_disabled_games (
+    guild_id BIGINT NOT NULL REFERENCES guilds (guild_id) ON DELETE CASCADE,
+    game TEXT NOT NULL,
+    UNIQUE (guild_id, game)
+);
+
+CREATE TABLE IF NOT EXISTS guild_score_channels (
+    guild_id BIGINT NOT NULL REFERENCES guilds (guild_id) ON DELETE CASCADE,
+    channel_id BIGINT NOT NULL,
+    UNIQUE (guild_id, channel_id)
+);
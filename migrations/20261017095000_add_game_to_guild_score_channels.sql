@@ -0,0 +1,9 @@
+ALTER TABLE guild_score_channels
+ADD COLUMN IF NOT EXISTS game TEXT;
+
+ALTER TABLE guild_score_channels
+DROP CONSTRAINT IF EXISTS guild_score_channels_guild_id_channel_id_key;
+
+-- A NULL game means the channel accepts scores for every game
+CREATE UNIQUE INDEX IF NOT EXISTS guild_score_channels_guild_id_channel_id_game_idx
+ON guild_score_channels (guild_id, channel_id, COALESCE(game, ''));
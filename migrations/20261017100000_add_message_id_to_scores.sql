@@ -0,0 +1,39 @@
+ALTER TABLE geogrid_scores
+ADD COLUMN IF NOT EXISTS message_id BIGINT;
+
+CREATE INDEX IF NOT EXISTS geogrid_scores_message_id_idx ON geogrid_scores (message_id);
+
+ALTER TABLE flagle_scores
+ADD COLUMN IF NOT EXISTS message_id BIGINT;
+
+CREATE INDEX IF NOT EXISTS flagle_scores_message_id_idx ON flagle_scores (message_id);
+
+ALTER TABLE foodguessr_scores
+ADD COLUMN IF NOT EXISTS message_id BIGINT;
+
+CREATE INDEX IF NOT EXISTS foodguessr_scores_message_id_idx ON foodguessr_scores (message_id);
+
+ALTER TABLE wordle_scores
+ADD COLUMN IF NOT EXISTS message_id BIGINT;
+
+CREATE INDEX IF NOT EXISTS wordle_scores_message_id_idx ON wordle_scores (message_id);
+
+ALTER TABLE connections_scores
+ADD COLUMN IF NOT EXISTS message_id BIGINT;
+
+CREATE INDEX IF NOT EXISTS connections_scores_message_id_idx ON connections_scores (message_id);
+
+ALTER TABLE worldle_scores
+ADD COLUMN IF NOT EXISTS message_id BIGINT;
+
+CREATE INDEX IF NOT EXISTS worldle_scores_message_id_idx ON worldle_scores (message_id);
+
+ALTER TABLE globle_scores
+ADD COLUMN IF NOT EXISTS message_id BIGINT;
+
+CREATE INDEX IF NOT EXISTS globle_scores_message_id_idx ON globle_scores (message_id);
+
+ALTER TABLE travle_scores
+ADD COLUMN IF NOT EXISTS message_id BIGINT;
+
+CREATE INDEX IF NOT EXISTS travle_scores_message_id_idx ON travle_scores (message_id);
@@ -0,0 +1,31 @@
+ALTER TABLE geogrid_scores
+ADD COLUMN IF NOT EXISTS channel_id BIGINT,
+ADD COLUMN IF NOT EXISTS submitted_at TIMESTAMPTZ;
+
+ALTER TABLE flagle_scores
+ADD COLUMN IF NOT EXISTS channel_id BIGINT,
+ADD COLUMN IF NOT EXISTS submitted_at TIMESTAMPTZ;
+
+ALTER TABLE foodguessr_scores
+ADD COLUMN IF NOT EXISTS channel_id BIGINT,
+ADD COLUMN IF NOT EXISTS submitted_at TIMESTAMPTZ;
+
+ALTER TABLE wordle_scores
+ADD COLUMN IF NOT EXISTS channel_id BIGINT,
+ADD COLUMN IF NOT EXISTS submitted_at TIMESTAMPTZ;
+
+ALTER TABLE connections_scores
+ADD COLUMN IF NOT EXISTS channel_id BIGINT,
+ADD COLUMN IF NOT EXISTS submitted_at TIMESTAMPTZ;
+
+ALTER TABLE worldle_scores
+ADD COLUMN IF NOT EXISTS channel_id BIGINT,
+ADD COLUMN IF NOT EXISTS submitted_at TIMESTAMPTZ;
+
+ALTER TABLE globle_scores
+ADD COLUMN IF NOT EXISTS channel_id BIGINT,
+ADD COLUMN IF NOT EXISTS submitted_at TIMESTAMPTZ;
+
+ALTER TABLE travle_scores
+ADD COLUMN IF NOT EXISTS channel_id BIGINT,
+ADD COLUMN IF NOT EXISTS submitted_at TIMESTAMPTZ;
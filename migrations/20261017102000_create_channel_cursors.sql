@@ -0,0 +1,7 @@
+-- The last message that was processed in each channel, so that messages posted while the bot was
+-- offline can be caught up on
+CREATE TABLE IF NOT EXISTS channel_cursors (
+    channel_id BIGINT PRIMARY KEY NOT NULL,
+    guild_id BIGINT NOT NULL,
+    last_message_id BIGINT NOT NULL
+);
@@ -0,0 +1,11 @@
+-- The messages that currently have the "best so far" reaction for each board, so that the reaction
+-- can be moved when the score is beaten or withdrawn. Tied best scores share the reaction unless
+-- ties are broken by time, so a board can have more than one best score message
+CREATE TABLE IF NOT EXISTS best_scores (
+    guild_id BIGINT NOT NULL REFERENCES guilds (guild_id) ON DELETE CASCADE,
+    game TEXT NOT NULL,
+    board INTEGER NOT NULL,
+    channel_id BIGINT NOT NULL,
+    message_id BIGINT NOT NULL,
+    UNIQUE (guild_id, game, board, message_id)
+);
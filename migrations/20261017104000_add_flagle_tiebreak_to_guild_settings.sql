@@ -0,0 +1,3 @@
+-- Whether tied Flagle scores should be separated by who submitted first, rather than sharing a place
+ALTER TABLE guild_settings
+ADD COLUMN IF NOT EXISTS flagle_tiebreak_by_time BOOLEAN NOT NULL DEFAULT FALSE;
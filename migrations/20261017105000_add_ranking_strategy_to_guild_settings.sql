@@ -0,0 +1,7 @@
+-- How all-time and windowed leaderboards rank players by their medals, and the points each medal is
+-- worth when ranking by medal points
+ALTER TABLE guild_settings
+ADD COLUMN IF NOT EXISTS ranking_strategy TEXT NOT NULL DEFAULT 'points',
+ADD COLUMN IF NOT EXISTS gold_points INTEGER NOT NULL DEFAULT 4,
+ADD COLUMN IF NOT EXISTS silver_points INTEGER NOT NULL DEFAULT 2,
+ADD COLUMN IF NOT EXISTS bronze_points INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,18 @@
+-- Each player's rating for each game, updated as boards close
+CREATE TABLE IF NOT EXISTS ratings (
+    guild_id BIGINT NOT NULL REFERENCES guilds (guild_id) ON DELETE CASCADE,
+    game TEXT NOT NULL,
+    user_id BIGINT NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+    rating DOUBLE PRECISION NOT NULL,
+    boards INTEGER NOT NULL,
+    PRIMARY KEY (guild_id, game, user_id)
+);
+
+-- The last board that has been counted towards the ratings for each game, so that each board is
+-- only counted once
+CREATE TABLE IF NOT EXISTS rating_progress (
+    guild_id BIGINT NOT NULL REFERENCES guilds (guild_id) ON DELETE CASCADE,
+    game TEXT NOT NULL,
+    last_board INTEGER NOT NULL,
+    PRIMARY KEY (guild_id, game)
+);
@@ -0,0 +1,116 @@
+use chrono::NaiveDate;
+use serenity::all::{
+    ChannelId, CommandOptionType, CreateCommandOption, Member, ResolvedOption, ResolvedValue, User,
+};
+
+use crate::game::{DynGame, GAMES};
+
+pub mod backfill;
+pub mod config;
+pub mod leaderboard;
+pub mod score;
+pub mod scores;
+pub mod stats;
+
+/// A required `game` option, with one choice per registered game.
+fn game_option(description: &str) -> CreateCommandOption {
+    GAMES.iter().fold(
+        CreateCommandOption::new(CommandOptionType::String, "game", description).required(true),
+        |option, game| option.add_string_choice(game.display_name(), game.slug()),
+    )
+}
+
+/// An optional `board` option, for picking a board by number.
+fn board_option(description: &str) -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::Integer, "board", description).min_int_value(0)
+}
+
+/// An optional `date` option, for picking a board by the date it was played on.
+fn date_option(description: &str) -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "date", description)
+}
+
+/// Work out which of `game`'s boards the `board` or `date` options refer to, or describe why they
+/// don't refer to one. Returns `Ok(None)` if neither option was given.
+fn find_board(game: &dyn DynGame, options: &[ResolvedOption<'_>]) -> Result<Option<usize>, String> {
+    let board = match (find_integer(options, "board"), find_string(options, "date")) {
+        (Some(_), Some(_)) => return Err("Specify either a board or a date, not both!".to_owned()),
+        (Some(_), None) if !game.numbered_boards() => {
+            return Err(format!(
+                "{} boards don't have numbers, specify a date instead!",
+                game.display_name(),
+            ));
+        }
+        (Some(board), None) => board as usize,
+        (None, Some(date)) => {
+            let date = parse_date(date)?;
+            game.board_on_date(date)
+                .ok_or_else(|| format!("There was no {} board on {}!", game.display_name(), date))?
+        }
+        (None, None) => return Ok(None),
+    };
+
+    if board > game.board_now() {
+        return Err("That board hasn't been released yet!".to_owned());
+    }
+
+    Ok(Some(board))
+}
+
+/// How to refer to one of `game`'s boards in a response.
+fn describe_board(game: &dyn DynGame, board: usize) -> String {
+    if game.numbered_boards() {
+        format!("board #{}", board)
+    } else {
+        "that day".to_owned()
+    }
+}
+
+/// Whether the member who triggered an interaction has the Manage Server permission. Discord hides
+/// admin commands from members without it, but servers can override that, so check again.
+fn can_manage_guild(member: Option<&Member>) -> bool {
+    member
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.manage_guild())
+}
+
+/// Parse a date given as a command option, or describe why it couldn't be parsed.
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("\"{}\" isn't a date in YYYY-MM-DD format!", date))
+}
+
+fn find_string<'a>(options: &[ResolvedOption<'a>], name: &str) -> Option<&'a str> {
+    options.iter().find_map(|opt| match opt.value {
+        ResolvedValue::String(value) if opt.name == name => Some(value),
+        _ => None,
+    })
+}
+
+fn find_integer(options: &[ResolvedOption<'_>], name: &str) -> Option<i64> {
+    options.iter().find_map(|opt| match opt.value {
+        ResolvedValue::Integer(value) if opt.name == name => Some(value),
+        _ => None,
+    })
+}
+
+fn find_bool(options: &[ResolvedOption<'_>], name: &str) -> Option<bool> {
+    options.iter().find_map(|opt| match opt.value {
+        ResolvedValue::Boolean(value) if opt.name == name => Some(value),
+        _ => None,
+    })
+}
+
+fn find_user<'a>(options: &[ResolvedOption<'a>], name: &str) -> Option<&'a User> {
+    options.iter().find_map(|opt| match opt.value {
+        ResolvedValue::User(user, _) if opt.name == name => Some(user),
+        _ => None,
+    })
+}
+
+fn find_channel(options: &[ResolvedOption<'_>], name: &str) -> Option<ChannelId> {
+    options.iter().find_map(|opt| match opt.value {
+        ResolvedValue::Channel(channel) if opt.name == name => Some(channel.id),
+        _ => None,
+    })
+}
@@ -0,0 +1,304 @@
+use std::{collections::HashSet, fmt};
+
+use chrono::{DateTime, NaiveTime, Utc};
+use serenity::all::{
+    Channel, ChannelId, ChannelType, CommandInteraction, CommandOptionType, Context, CreateCommand,
+    CreateCommandOption, CreateMessage, EditInteractionResponse, GuildId, Mention, Permissions,
+};
+use sqlx::PgPool;
+use tracing::{error, info, instrument, warn};
+
+use super::{can_manage_guild, find_channel, find_string, parse_date};
+use crate::{
+    game::{rating, RecordScoreError, ScoreInsertionError, Submission, GAMES},
+    history::{self, History},
+    reactions,
+    settings::GuildSettings,
+};
+
+pub const NAME: &str = "backfill";
+
+/// How many messages to scan between progress updates.
+const PROGRESS_INTERVAL: usize = 1000;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .description("Import scores that were posted in a channel while the bot wasn't watching")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Channel,
+                "channel",
+                "The channel to import scores from",
+            )
+            .channel_types(vec![ChannelType::Text])
+            .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "since",
+                "Import scores posted on or after this date, as YYYY-MM-DD",
+            )
+            .required(true),
+        )
+}
+
+/// Backfilling can take far longer than Discord allows for an initial response, or even for
+/// editing the response later, so unlike the other commands this one defers its response, edits it
+/// once the backfill has started, and then posts progress and a summary in the channel that the
+/// command was run in.
+pub async fn run(ctx: &Context, command: &CommandInteraction, db_pool: &PgPool) {
+    let (guild_id, channel_id, since) = match prepare(ctx, command).await {
+        Ok(prepared) => prepared,
+        Err(content) => {
+            respond(ctx, command, content).await;
+            return;
+        }
+    };
+
+    respond(
+        ctx,
+        command,
+        format!(
+            "Backfilling {} since {}. Progress and results will be posted in this channel.",
+            Mention::Channel(channel_id),
+            since.format("%d %b %Y"),
+        ),
+    )
+    .await;
+
+    let content = match backfill(
+        ctx,
+        db_pool,
+        guild_id,
+        channel_id,
+        since,
+        command.channel_id,
+    )
+    .await
+    {
+        Ok(summary) => format!(
+            "Finished backfilling {} since {}: {}",
+            Mention::Channel(channel_id),
+            since.format("%d %b %Y"),
+            summary,
+        ),
+        Err(error) => {
+            error!(%error, "failed to backfill scores");
+            format!(
+                "Failed to read messages from {}. Check that I have permission to read its \
+                 message history!",
+                Mention::Channel(channel_id),
+            )
+        }
+    };
+
+    post(ctx, command.channel_id, content).await;
+}
+
+async fn respond(ctx: &Context, command: &CommandInteraction, content: String) {
+    match command
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await
+    {
+        Ok(_) => info!("responded to command"),
+        Err(error) => error!(%error, "failed to respond to command"),
+    }
+}
+
+async fn post(ctx: &Context, channel_id: ChannelId, content: String) {
+    match channel_id
+        .send_message(&ctx.http, CreateMessage::new().content(content))
+        .await
+    {
+        Ok(_) => info!(%channel_id, "posted backfill update"),
+        Err(error) => error!(%error, %channel_id, "failed to post backfill update"),
+    }
+}
+
+/// Check the command's options and defer the response, or describe why the command can't be run.
+async fn prepare(
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(GuildId, ChannelId, DateTime<Utc>), String> {
+    if let Err(error) = command.defer(&ctx.http).await {
+        error!(%error, "failed to defer response to command");
+    }
+
+    let Some(guild_id) = command.guild_id else {
+        warn!("cannot continue processing interaction without guild ID");
+        return Err("This command can only be run in a server!".to_owned());
+    };
+
+    if !can_manage_guild(command.member.as_deref()) {
+        return Err("You need the Manage Server permission to backfill scores!".to_owned());
+    }
+
+    let options = command.data.options();
+    let Some(channel_id) = find_channel(&options, "channel") else {
+        return Err("You must specify a channel to backfill!".to_owned());
+    };
+    let Some(since) = find_string(&options, "since") else {
+        return Err("You must specify a date to backfill from!".to_owned());
+    };
+    let since = parse_date(since)?.and_time(NaiveTime::MIN).and_utc();
+
+    Ok((guild_id, channel_id, since))
+}
+
+/// Run every message in `channel_id` posted since `since` through the score parsers, recording
+/// any scores using the time they were posted, or last edited. Scores for games that aren't
+/// accepted in `channel_id` are skipped, the same as they would have been if the bot had been
+/// watching. Progress is posted in `progress_channel_id` every [`PROGRESS_INTERVAL`] messages.
+#[instrument(skip(ctx, db_pool))]
+pub async fn backfill(
+    ctx: &Context,
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    since: DateTime<Utc>,
+    progress_channel_id: ChannelId,
+) -> serenity::Result<Summary> {
+    let settings = GuildSettings::get(db_pool, guild_id)
+        .await
+        .unwrap_or_else(|error| {
+            error!(%error, "failed to fetch guild settings, falling back to defaults");
+            GuildSettings::default()
+        });
+
+    // The channel might only accept scores because it's in an allowed category
+    let parent_id = match channel_id.to_channel(&ctx.http).await {
+        Ok(Channel::Guild(channel)) => channel.parent_id,
+        Ok(_) => None,
+        Err(error) => {
+            warn!(%error, "failed to fetch channel to check its parent");
+            None
+        }
+    };
+
+    let mut summary = Summary::default();
+    // The earliest board of each game that a score was imported for, since the ratings will need
+    // to be worked out again from there
+    let mut earliest = vec![None::<usize>; GAMES.len()];
+    // Every board of each game that a score was imported for, since the best scores might have
+    // changed
+    let mut boards = HashSet::<(usize, usize)>::new();
+    let mut history = History::after(channel_id, history::message_id_at(since));
+
+    loop {
+        let messages = history.next_page(&ctx.http).await?;
+        if messages.is_empty() {
+            break;
+        }
+
+        for msg in messages {
+            summary.scanned += 1;
+
+            for (i, game) in GAMES.iter().enumerate() {
+                if !settings.game_enabled(game.slug()) {
+                    continue;
+                }
+
+                if !settings.accepts_scores_in(game.slug(), channel_id, parent_id) {
+                    if game.is_score(&msg.content) {
+                        summary.wrong_channel += 1;
+                        break;
+                    }
+                    continue;
+                }
+
+                match game
+                    .record_score(
+                        db_pool,
+                        guild_id,
+                        &msg.author,
+                        &msg.content,
+                        Submission::edited(&msg),
+                    )
+                    .await
+                {
+                    Ok(board) => {
+                        summary.imported += 1;
+                        earliest[i] =
+                            Some(earliest[i].map_or(board, |earliest| earliest.min(board)));
+                        boards.insert((i, board));
+                    }
+                    Err(RecordScoreError::NotAScore(_)) => continue,
+                    Err(RecordScoreError::Insertion(ScoreInsertionError::Duplicate)) => {
+                        summary.duplicates += 1
+                    }
+                    Err(error) => {
+                        warn!(%error, message_id = %msg.id, "failed to import score");
+                        summary.failed += 1;
+                    }
+                }
+
+                break;
+            }
+
+            if summary.scanned % PROGRESS_INTERVAL == 0 {
+                post(
+                    ctx,
+                    progress_channel_id,
+                    format!(
+                        "Still backfilling {}, so far: {}",
+                        Mention::Channel(channel_id),
+                        summary,
+                    ),
+                )
+                .await;
+            }
+        }
+    }
+
+    info!(?summary, "finished backfilling");
+
+    if settings.reactions_enabled {
+        for (i, board) in boards {
+            reactions::update_best(&ctx.http, db_pool, GAMES[i], guild_id, board).await;
+        }
+    }
+
+    for (game, earliest) in GAMES.iter().zip(earliest) {
+        if let Some(board) = earliest {
+            rating::board_changed(db_pool, guild_id, *game, board).await;
+        }
+    }
+
+    Ok(summary)
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Summary {
+    pub scanned: usize,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub wrong_channel: usize,
+    pub failed: usize,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "scanned {} messages, imported {} new scores, skipped {} already recorded",
+            self.scanned, self.imported, self.duplicates,
+        )?;
+
+        if self.wrong_channel > 0 {
+            write!(
+                f,
+                ", skipped {} for games that aren't recorded in this channel",
+                self.wrong_channel,
+            )?;
+        }
+
+        if self.failed > 0 {
+            write!(f, ", and failed to import {}", self.failed)?;
+        }
+
+        write!(f, ".")
+    }
+}
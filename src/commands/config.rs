@@ -0,0 +1,431 @@
+use std::fmt::Write as _;
+
+use serenity::all::{
+    ChannelType, CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateInteractionResponseMessage, GuildId, Mention, Permissions, ResolvedOption,
+    ResolvedValue,
+};
+use sqlx::PgPool;
+use tracing::{error, warn};
+
+use super::{can_manage_guild, find_bool, find_channel, find_integer, find_string, game_option};
+use crate::{
+    game::{self, leaderboards::RankingStrategy, rating, GAMES},
+    settings::{self, GuildSettings, SettingsError},
+};
+
+pub const NAME: &str = "config";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .description("Configure the bot for this server")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .dm_permission(false)
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "show",
+            "Show the current configuration",
+        ))
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "game",
+                "Enable or disable a game in this server",
+            )
+            .add_sub_option(game_option("The game to enable or disable"))
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "enabled",
+                    "Whether scores and leaderboards for the game should be available",
+                )
+                .required(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "results_channel",
+                "Set the channel that each day's final results are posted in",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Channel,
+                    "channel",
+                    "The channel to post results in (leave empty to stop posting results)",
+                )
+                .channel_types(vec![ChannelType::Text]),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommandGroup,
+                "score_channels",
+                "Choose the channels that scores are accepted in",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "add",
+                    "Accept scores in a channel or category",
+                )
+                .add_sub_option(score_channel_option())
+                .add_sub_option(score_channel_game_option()),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "remove",
+                    "Stop accepting scores in a channel or category",
+                )
+                .add_sub_option(score_channel_option())
+                .add_sub_option(score_channel_game_option()),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "clear",
+                    "Accept scores in every channel again",
+                )
+                .add_sub_option(score_channel_game_option()),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "reactions",
+                "Turn reactions to submitted scores on or off",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "enabled",
+                    "Whether to react to submitted scores",
+                )
+                .required(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "flagle_tiebreak",
+                "Break ties on Flagle leaderboards, medals and ratings by who submitted first",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "enabled",
+                    "Whether the earliest submission wins a tie, instead of tied scores sharing a \
+                     place",
+                )
+                .required(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "ranking",
+                "Choose how all-time, weekly and monthly leaderboards rank players",
+            )
+            .add_sub_option(
+                RankingStrategy::SLUGS.iter().fold(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "strategy",
+                        "What to rank players by",
+                    )
+                    .required(true),
+                    |option, (slug, name)| option.add_string_choice(*name, *slug),
+                ),
+            )
+            .add_sub_option(points_option("gold_points", "gold", 4))
+            .add_sub_option(points_option("silver_points", "silver", 2))
+            .add_sub_option(points_option("bronze_points", "bronze", 1)),
+        )
+}
+
+fn points_option(name: &str, medal: &str, default: usize) -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::Integer,
+        name,
+        format!(
+            "Points for each {} medal, when ranking by medal points (default {})",
+            medal, default
+        ),
+    )
+    .min_int_value(0)
+    .max_int_value(1000)
+}
+
+fn score_channel_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::Channel,
+        "channel",
+        "The channel, or a category to include every channel in it",
+    )
+    .channel_types(vec![ChannelType::Text, ChannelType::Category])
+    .required(true)
+}
+
+fn score_channel_game_option() -> CreateCommandOption {
+    game_option("Only apply to this game (applies to every game by default)").required(false)
+}
+
+pub async fn run(
+    command: &CommandInteraction,
+    db_pool: &PgPool,
+    guild_id: GuildId,
+) -> CreateInteractionResponseMessage {
+    if !can_manage_guild(command.member.as_deref()) {
+        return CreateInteractionResponseMessage::new()
+            .content("You need the Manage Server permission to change the configuration!")
+            .ephemeral(true);
+    }
+
+    let options = command.data.options();
+    let Some(ResolvedOption { name, value, .. }) = options.first() else {
+        return CreateInteractionResponseMessage::new().content("An unexpected error occurred");
+    };
+
+    let result = match (*name, value) {
+        ("show", ResolvedValue::SubCommand(_)) => {
+            return match GuildSettings::get(db_pool, guild_id).await {
+                Ok(settings) => CreateInteractionResponseMessage::new()
+                    .embed(settings_embed(&settings))
+                    .ephemeral(true),
+                Err(error) => {
+                    error!(%error, "failed to fetch guild settings");
+                    CreateInteractionResponseMessage::new().content("An unexpected error occurred.")
+                }
+            };
+        }
+        ("game", ResolvedValue::SubCommand(options)) => {
+            let Some(game) = find_string(options, "game").and_then(game::by_slug) else {
+                warn!("cannot respond to command without a valid game parameter");
+                return CreateInteractionResponseMessage::new()
+                    .content("You must specify a game to enable or disable!");
+            };
+            let enabled = find_bool(options, "enabled").unwrap_or(true);
+
+            settings::set_game_enabled(db_pool, guild_id, game.slug(), enabled)
+                .await
+                .map(|()| {
+                    format!(
+                        "{} is now {} in this server.",
+                        game.display_name(),
+                        if enabled { "enabled" } else { "disabled" },
+                    )
+                })
+        }
+        ("results_channel", ResolvedValue::SubCommand(options)) => {
+            let channel_id = find_channel(options, "channel");
+
+            settings::set_results_channel(db_pool, guild_id, channel_id)
+                .await
+                .map(|()| match channel_id {
+                    Some(channel_id) => format!(
+                        "Each day's final results will be posted in {}.",
+                        Mention::Channel(channel_id)
+                    ),
+                    None => "Final results will no longer be posted.".to_owned(),
+                })
+        }
+        ("score_channels", ResolvedValue::SubCommandGroup(options)) => {
+            let Some(ResolvedOption {
+                name,
+                value: ResolvedValue::SubCommand(options),
+                ..
+            }) = options.first()
+            else {
+                return CreateInteractionResponseMessage::new()
+                    .content("An unexpected error occurred.");
+            };
+
+            let game = match find_string(options, "game") {
+                Some(slug) => match game::by_slug(slug) {
+                    Some(game) => Some(game),
+                    None => {
+                        return CreateInteractionResponseMessage::new()
+                            .content(format!("Unknown game \"{}\"!", slug));
+                    }
+                },
+                None => None,
+            };
+            let slug = game.map(|game| game.slug());
+            let scores = match game {
+                Some(game) => format!("{} scores", game.display_name()),
+                None => "Scores".to_owned(),
+            };
+
+            if *name == "clear" {
+                settings::clear_score_channels(db_pool, guild_id, slug)
+                    .await
+                    .map(|()| format!("{} will be accepted in every channel.", scores))
+            } else {
+                let Some(channel_id) = find_channel(options, "channel") else {
+                    return CreateInteractionResponseMessage::new()
+                        .content("You must specify a channel!");
+                };
+
+                if *name == "add" {
+                    settings::add_score_channel(db_pool, guild_id, channel_id, slug)
+                        .await
+                        .map(|()| {
+                            format!(
+                                "{} will be accepted in {}.",
+                                scores,
+                                Mention::Channel(channel_id)
+                            )
+                        })
+                } else {
+                    settings::remove_score_channel(db_pool, guild_id, channel_id, slug)
+                        .await
+                        .map(|()| {
+                            format!(
+                                "{} will no longer be accepted in {}.",
+                                scores,
+                                Mention::Channel(channel_id)
+                            )
+                        })
+                }
+            }
+        }
+        ("reactions", ResolvedValue::SubCommand(options)) => {
+            let enabled = find_bool(options, "enabled").unwrap_or(true);
+
+            settings::set_reactions_enabled(db_pool, guild_id, enabled)
+                .await
+                .map(|()| {
+                    if enabled {
+                        "Submitted scores will get reactions.".to_owned()
+                    } else {
+                        "Submitted scores will no longer get reactions.".to_owned()
+                    }
+                })
+        }
+        ("flagle_tiebreak", ResolvedValue::SubCommand(options)) => {
+            let enabled = find_bool(options, "enabled").unwrap_or(false);
+
+            let result = settings::set_flagle_tiebreak_by_time(db_pool, guild_id, enabled).await;
+            if result.is_ok() {
+                // Places can change on every board, so the ratings are worked out from the start
+                for game in GAMES.iter().filter(|game| game.score_table().time_tiebreak) {
+                    rating::board_changed(db_pool, guild_id, *game, 0).await;
+                }
+            }
+
+            result.map(|()| {
+                if enabled {
+                    "Tied Flagle scores will be won by whoever submitted first.".to_owned()
+                } else {
+                    "Tied Flagle scores will share a place.".to_owned()
+                }
+            })
+        }
+        ("ranking", ResolvedValue::SubCommand(options)) => {
+            let points = |name, default| {
+                find_integer(options, name).map_or(default, |points| points as usize)
+            };
+            let points = (
+                points("gold_points", 4),
+                points("silver_points", 2),
+                points("bronze_points", 1),
+            );
+
+            let Some(ranking) = find_string(options, "strategy")
+                .and_then(|slug| RankingStrategy::from_slug(slug, points))
+            else {
+                warn!("cannot respond to command without a valid strategy parameter");
+                return CreateInteractionResponseMessage::new()
+                    .content("You must specify a ranking strategy!");
+            };
+
+            settings::set_ranking_strategy(db_pool, guild_id, ranking)
+                .await
+                .map(|()| format!("All-time leaderboards are now ranked by: {}.", ranking))
+        }
+        _ => {
+            return CreateInteractionResponseMessage::new()
+                .content("An unexpected error occurred.");
+        }
+    };
+
+    respond(result)
+}
+
+fn respond(result: Result<String, SettingsError>) -> CreateInteractionResponseMessage {
+    match result {
+        Ok(content) => CreateInteractionResponseMessage::new().content(content),
+        Err(error) => {
+            error!(%error, "failed to update guild settings");
+            CreateInteractionResponseMessage::new().content("An unexpected error occurred.")
+        }
+    }
+}
+
+fn settings_embed(settings: &GuildSettings) -> CreateEmbed {
+    let mut games = String::new();
+    for game in GAMES {
+        writeln!(
+            &mut games,
+            "- {}: {}",
+            game.display_name(),
+            if settings.game_enabled(game.slug()) {
+                "enabled"
+            } else {
+                "disabled"
+            },
+        )
+        .expect("should be able to write into String");
+    }
+
+    let score_channels = if settings.score_channels.is_empty() {
+        "Any channel".to_owned()
+    } else {
+        settings
+            .score_channels
+            .iter()
+            .map(|channel| {
+                let game = channel
+                    .game
+                    .as_deref()
+                    .and_then(game::by_slug)
+                    .map_or("every game", |game| game.display_name());
+                format!("{} ({})", Mention::Channel(channel.channel_id), game)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    CreateEmbed::new()
+        .title("Server Configuration")
+        .field("Games", games, false)
+        .field(
+            "Results channel",
+            settings
+                .results_channel
+                .map(|channel_id| Mention::Channel(channel_id).to_string())
+                .unwrap_or_else(|| "None".to_owned()),
+            true,
+        )
+        .field("Score channels", score_channels, true)
+        .field(
+            "Reactions",
+            if settings.reactions_enabled {
+                "On"
+            } else {
+                "Off"
+            },
+            true,
+        )
+        .field(
+            "Flagle ties",
+            if settings.flagle_tiebreak_by_time {
+                "Earliest submission wins"
+            } else {
+                "Shared"
+            },
+            true,
+        )
+        .field("All-time ranking", settings.ranking.to_string(), true)
+}
@@ -0,0 +1,811 @@
+use std::num::NonZeroU64;
+
+use serenity::all::{
+    ButtonStyle, CommandInteraction, CommandOptionType, ComponentInteraction, Context,
+    CreateActionRow, CreateAllowedMentions, CreateButton, CreateCommand, CreateCommandOption,
+    CreateInteractionResponse, CreateInteractionResponseMessage, GuildId, ResolvedOption,
+    ResolvedValue, User, UserId,
+};
+use sqlx::PgPool;
+use tracing::{error, info, instrument, warn};
+
+use super::{
+    board_option, date_option, find_board, find_bool, find_string, game_option, parse_date,
+};
+use crate::{
+    game::{
+        self,
+        leaderboards::{Listing, Overall, Period, Window},
+        percentile::PercentileIndex,
+        rating::Ratings,
+        DynGame, GAMES,
+    },
+    settings::GuildSettings,
+};
+
+pub const NAME: &str = "leaderboard";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .description("View the leaderboard")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "today",
+                "View the leaderboard for today",
+            )
+            .add_sub_option(game_option("The game to view the leaderboard for")),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "day",
+                "View the leaderboard for a past day (yesterday by default)",
+            )
+            .add_sub_option(game_option("The game to view the leaderboard for"))
+            .add_sub_option(board_option(
+                "The number of the board to view the leaderboard for",
+            ))
+            .add_sub_option(date_option(
+                "The date to view the leaderboard for, as YYYY-MM-DD",
+            )),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "all_time",
+                "View the all-time leaderboard",
+            )
+            .add_sub_option(game_option("The game to view the leaderboard for"))
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "include_today",
+                "Include today's scores in the all-time leaderboard?",
+            ))
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "include_late",
+                "Include score submissions that were entered after the day ended?",
+            )),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "week",
+                "View the leaderboard for this week so far",
+            )
+            .add_sub_option(game_option("The game to view the leaderboard for"))
+            .add_sub_option(include_late_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "month",
+                "View the leaderboard for this month so far",
+            )
+            .add_sub_option(game_option("The game to view the leaderboard for"))
+            .add_sub_option(include_late_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "range",
+                "View the leaderboard for a range of dates",
+            )
+            .add_sub_option(game_option("The game to view the leaderboard for"))
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "from",
+                    "The first date to include, as YYYY-MM-DD",
+                )
+                .required(true),
+            )
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "to",
+                "The last date to include, as YYYY-MM-DD (today by default)",
+            ))
+            .add_sub_option(include_late_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "overall",
+                "View the leaderboard combining medals from every game",
+            )
+            .add_sub_option(period_option("The period to count medals over"))
+            .add_sub_option(include_late_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "percentile",
+                "View the leaderboard of average percentiles, which can be compared across games",
+            )
+            .add_sub_option(
+                game_option("Only include this game (includes every game by default)")
+                    .required(false),
+            )
+            .add_sub_option(period_option("The period to average percentiles over"))
+            .add_sub_option(include_late_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "rating",
+                "View everyone's rating, which goes up and down depending on who they beat",
+            )
+            .add_sub_option(game_option("The game to view the ratings for")),
+        )
+}
+
+/// An optional `period` option for leaderboards that combine games, which can't have a range of
+/// boards since each game numbers its boards differently.
+fn period_option(description: &str) -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::String,
+        "period",
+        format!("{} (all time by default)", description),
+    )
+    .add_string_choice("All time", "all_time")
+    .add_string_choice("This week", "week")
+    .add_string_choice("This month", "month")
+}
+
+fn find_period(options: &[ResolvedOption<'_>]) -> Period {
+    match find_string(options, "period") {
+        Some("week") => Period::Week,
+        Some("month") => Period::Month,
+        _ => Period::AllTime,
+    }
+}
+
+fn include_late_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::Boolean,
+        "include_late",
+        "Include score submissions that were entered after the day ended?",
+    )
+}
+
+pub async fn run(
+    command: &CommandInteraction,
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    settings: &GuildSettings,
+) -> CreateInteractionResponseMessage {
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        name,
+        value: ResolvedValue::SubCommand(options),
+        ..
+    }) = options.first()
+    else {
+        return CreateInteractionResponseMessage::new().content("An unexpected error occurred");
+    };
+
+    if *name == "overall" || *name == "percentile" {
+        let include_late = find_bool(options, "include_late").unwrap_or(false);
+        let query = if *name == "overall" {
+            Query::Overall {
+                period: find_period(options),
+                include_late,
+            }
+        } else {
+            let game = match find_string(options, "game") {
+                Some(slug) => match game::by_slug(slug) {
+                    Some(game) if settings.game_enabled(game.slug()) => Some(game),
+                    Some(game) => {
+                        return CreateInteractionResponseMessage::new().content(format!(
+                            "{} is disabled in this server!",
+                            game.display_name()
+                        ));
+                    }
+                    None => {
+                        return CreateInteractionResponseMessage::new()
+                            .content(format!("Unknown game \"{}\"!", slug));
+                    }
+                },
+                None => None,
+            };
+
+            Query::Percentile {
+                game,
+                period: find_period(options),
+                include_late,
+            }
+        };
+
+        return match query.calculate(db_pool, guild_id).await {
+            Some(listing) => page_message(&listing, 0, &command.user, query),
+            None => {
+                CreateInteractionResponseMessage::new().content("An unexpected error occurred.")
+            }
+        };
+    }
+
+    let Some(slug) = find_string(options, "game") else {
+        warn!("cannot respond to command without a value for the game parameter");
+        return CreateInteractionResponseMessage::new()
+            .content("You must specify a game in order to view the leaderboard!");
+    };
+
+    let Some(game) = game::by_slug(slug) else {
+        return CreateInteractionResponseMessage::new()
+            .content(format!("Unknown game \"{}\"!", slug));
+    };
+
+    if !settings.game_enabled(game.slug()) {
+        return CreateInteractionResponseMessage::new().content(format!(
+            "{} is disabled in this server!",
+            game.display_name()
+        ));
+    }
+
+    let query = if *name == "today" || *name == "day" {
+        let board = if *name == "today" {
+            game.board_now()
+        } else {
+            match find_board(game, options) {
+                Ok(Some(board)) => board,
+                Ok(None) => game.board_now().saturating_sub(1),
+                Err(message) => return CreateInteractionResponseMessage::new().content(message),
+            }
+        };
+
+        Query::Daily { game, board }
+    } else if *name == "all_time" {
+        Query::AllTime {
+            game,
+            include_today: find_bool(options, "include_today").unwrap_or(true),
+            include_late: find_bool(options, "include_late").unwrap_or(false),
+        }
+    } else if *name == "week" || *name == "month" || *name == "range" {
+        let include_late = find_bool(options, "include_late").unwrap_or(false);
+        let today = game.today();
+        let board_now = game.board_now();
+
+        let window = if *name == "week" {
+            Window::week(today, board_now, include_late)
+        } else if *name == "month" {
+            Window::month(today, board_now, include_late)
+        } else {
+            let start = match find_string(options, "from").map(parse_date) {
+                Some(Ok(start)) => start,
+                Some(Err(message)) => {
+                    return CreateInteractionResponseMessage::new().content(message)
+                }
+                None => {
+                    return CreateInteractionResponseMessage::new()
+                        .content("You must specify a date to start from!");
+                }
+            };
+
+            if start > today {
+                return CreateInteractionResponseMessage::new()
+                    .content("The range can't start in the future!");
+            }
+
+            let end = match find_string(options, "to").map(parse_date) {
+                Some(Ok(end)) => end.min(today),
+                Some(Err(message)) => {
+                    return CreateInteractionResponseMessage::new().content(message)
+                }
+                None => today,
+            };
+
+            if start > end {
+                return CreateInteractionResponseMessage::new()
+                    .content("The range must start before it ends!");
+            }
+
+            let Some(last_board) = game.board_on_date(end) else {
+                return CreateInteractionResponseMessage::new().content(format!(
+                    "There were no {} boards in that range!",
+                    game.display_name(),
+                ));
+            };
+
+            Window {
+                period: Period::Range,
+                first_board: game.board_on_date(start).unwrap_or(0),
+                last_board: last_board.min(board_now),
+                start,
+                end,
+                include_late,
+            }
+        };
+
+        Query::Windowed { game, window }
+    } else if *name == "rating" {
+        Query::Rating { game }
+    } else {
+        return CreateInteractionResponseMessage::new().content("An unexpected error occurred.");
+    };
+
+    match query.calculate(db_pool, guild_id).await {
+        Some(listing) => page_message(&listing, 0, &command.user, query),
+        None => CreateInteractionResponseMessage::new().content("An unexpected error occurred."),
+    }
+}
+
+pub fn is_own_component(component: &ComponentInteraction) -> bool {
+    component
+        .data
+        .custom_id
+        .strip_prefix(NAME)
+        .is_some_and(|rest| rest.starts_with(':'))
+}
+
+/// Show another page of a leaderboard, when one of the buttons under it is pressed.
+#[instrument(skip_all)]
+pub async fn turn_page(ctx: &Context, component: &ComponentInteraction, db_pool: &PgPool) {
+    let response = page_response(component, db_pool)
+        .await
+        .unwrap_or_else(|content| {
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(true),
+            )
+        });
+
+    match component.create_response(&ctx.http, response).await {
+        Ok(_) => info!("responded to leaderboard button"),
+        Err(error) => error!(%error, "failed to respond to leaderboard button"),
+    }
+}
+
+async fn page_response(
+    component: &ComponentInteraction,
+    db_pool: &PgPool,
+) -> Result<CreateInteractionResponse, String> {
+    let Some(guild_id) = component.guild_id else {
+        warn!("cannot continue processing component interaction without guild ID");
+        return Err("Leaderboards can only be viewed in a server!".to_owned());
+    };
+
+    let Some((action, page, owner, query)) = parse_custom_id(&component.data.custom_id) else {
+        warn!(custom_id = %component.data.custom_id, "failed to parse leaderboard button");
+        return Err("An unexpected error occurred.".to_owned());
+    };
+
+    // The game might have been disabled since the leaderboard was posted
+    if let Some(game) = query.game() {
+        let settings = GuildSettings::get(db_pool, guild_id)
+            .await
+            .unwrap_or_else(|error| {
+                error!(%error, "failed to fetch guild settings, falling back to defaults");
+                GuildSettings::default()
+            });
+
+        if !settings.game_enabled(game.slug()) {
+            return Err(format!(
+                "{} is disabled in this server!",
+                game.display_name()
+            ));
+        }
+    }
+
+    // Work the leaderboard out again rather than storing it, which means a page might have moved
+    // on a little if more scores have come in since
+    let Some(listing) = query.calculate(db_pool, guild_id).await else {
+        return Err("An unexpected error occurred.".to_owned());
+    };
+
+    let page = match action {
+        "previous" => page.saturating_sub(1),
+        "next" => page + 1,
+        "me" => listing
+            .page_of(component.user.id)
+            .ok_or_else(|| "You're not on this leaderboard!".to_owned())?,
+        _ => page,
+    };
+
+    // Only the person who asked for a leaderboard gets to turn its pages, since everyone else in
+    // the channel is looking at it too. Anyone else gets their own copy that only they can see,
+    // which they then own, so the only place a message ever shows is its owner's
+    let message = page_message(&listing, page, &component.user, query);
+    Ok(if edits_in_place(owner, component.user.id) {
+        CreateInteractionResponse::UpdateMessage(message)
+    } else {
+        CreateInteractionResponse::Message(message.ephemeral(true))
+    })
+}
+
+/// Whether pressing a button on a leaderboard owned by `owner` turns the page of that message,
+/// rather than sending `clicker` a copy of their own. Messages that nobody owns are never edited.
+fn edits_in_place(owner: Option<UserId>, clicker: UserId) -> bool {
+    owner == Some(clicker)
+}
+
+/// The message showing `page` of a leaderboard, along with where `viewer` is on it. The message
+/// belongs to `viewer`, so nobody else can turn its pages and replace their place with another.
+fn page_message(
+    listing: &Listing,
+    page: usize,
+    viewer: &User,
+    query: Query,
+) -> CreateInteractionResponseMessage {
+    let page = page.min(listing.pages() - 1);
+
+    CreateInteractionResponseMessage::new()
+        .embed(listing.page_for(page, viewer))
+        .components(buttons(listing, page, Some(viewer.id), query))
+        .allowed_mentions(CreateAllowedMentions::new())
+}
+
+/// The buttons for moving between the pages of a leaderboard that's showing `page`, if it has more
+/// than one. Only `owner` can turn the message's own pages; anyone else who presses a button is
+/// sent a copy of the page instead. Messages that nobody asked for, like the daily results posts,
+/// have no owner.
+pub fn buttons(
+    listing: &Listing,
+    page: usize,
+    owner: Option<UserId>,
+    query: Query,
+) -> Vec<CreateActionRow> {
+    let pages = listing.pages();
+    if pages <= 1 {
+        return Vec::new();
+    }
+
+    let button = |action| CreateButton::new(custom_id(action, page, owner, query));
+
+    vec![CreateActionRow::Buttons(vec![
+        button("previous")
+            .label("Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(page == 0),
+        button("page")
+            .label(format!("Page {} of {}", page + 1, pages))
+            .style(ButtonStyle::Secondary)
+            .disabled(true),
+        button("next")
+            .label("Next")
+            .style(ButtonStyle::Secondary)
+            .disabled(page + 1 >= pages),
+        button("me").label("Jump to me").style(ButtonStyle::Primary),
+    ])]
+}
+
+/// Which leaderboard is being shown, with everything needed to calculate it again when one of its
+/// buttons is pressed.
+#[derive(Clone, Copy)]
+pub enum Query {
+    Daily {
+        game: &'static dyn DynGame,
+        board: usize,
+    },
+    AllTime {
+        game: &'static dyn DynGame,
+        include_today: bool,
+        include_late: bool,
+    },
+    Windowed {
+        game: &'static dyn DynGame,
+        window: Window,
+    },
+    Overall {
+        period: Period,
+        include_late: bool,
+    },
+    /// A percentile leaderboard for one game, or for every enabled game if `game` is `None`.
+    Percentile {
+        game: Option<&'static dyn DynGame>,
+        period: Period,
+        include_late: bool,
+    },
+    Rating {
+        game: &'static dyn DynGame,
+    },
+}
+
+impl Query {
+    /// The game this leaderboard is for, if it's for a single game.
+    fn game(self) -> Option<&'static dyn DynGame> {
+        match self {
+            Query::Daily { game, .. }
+            | Query::AllTime { game, .. }
+            | Query::Windowed { game, .. }
+            | Query::Rating { game } => Some(game),
+            Query::Percentile { game, .. } => game,
+            Query::Overall { .. } => None,
+        }
+    }
+
+    async fn calculate(self, db_pool: &PgPool, guild_id: GuildId) -> Option<Listing> {
+        match self {
+            Query::Daily { game, board } => game
+                .daily_leaderboard(db_pool, guild_id, board)
+                .await
+                .map_err(|error| error!(%error, "failed to calculate daily leaderboard"))
+                .ok(),
+            Query::AllTime {
+                game,
+                include_today,
+                include_late,
+            } => game
+                .all_time_leaderboard(db_pool, guild_id, include_today, include_late)
+                .await
+                .map_err(|error| error!(%error, "failed to calculate all-time leaderboard"))
+                .ok(),
+            Query::Windowed { game, window } => game
+                .windowed_leaderboard(db_pool, guild_id, window)
+                .await
+                .map_err(|error| error!(%error, "failed to calculate windowed leaderboard"))
+                .ok(),
+            Query::Overall {
+                period,
+                include_late,
+            } => {
+                let games = enabled_games(db_pool, guild_id).await;
+                Overall::calculate(db_pool, guild_id, &games, period, include_late)
+                    .await
+                    .map(Into::into)
+                    .map_err(|error| error!(%error, "failed to calculate overall leaderboard"))
+                    .ok()
+            }
+            Query::Percentile {
+                game,
+                period,
+                include_late,
+            } => {
+                let games = match game {
+                    Some(game) => vec![game],
+                    None => enabled_games(db_pool, guild_id).await,
+                };
+                PercentileIndex::calculate(db_pool, guild_id, &games, period, include_late)
+                    .await
+                    .map(Into::into)
+                    .map_err(|error| error!(%error, "failed to calculate percentile leaderboard"))
+                    .ok()
+            }
+            Query::Rating { game } => Ratings::calculate(db_pool, guild_id, game)
+                .await
+                .map(Into::into)
+                .map_err(|error| error!(%error, "failed to calculate ratings"))
+                .ok(),
+        }
+    }
+}
+
+async fn enabled_games(db_pool: &PgPool, guild_id: GuildId) -> Vec<&'static dyn DynGame> {
+    let settings = GuildSettings::get(db_pool, guild_id)
+        .await
+        .unwrap_or_else(|error| {
+            error!(%error, "failed to fetch guild settings, falling back to defaults");
+            GuildSettings::default()
+        });
+
+    GAMES
+        .iter()
+        .copied()
+        .filter(|game| settings.game_enabled(game.slug()))
+        .collect()
+}
+
+/// Button custom IDs look like `leaderboard:next:2:1234567890:flagle:day:1234`,
+/// `leaderboard:next:2:-:overall:week:0` or `leaderboard:next:2:-:percentile:all:week:0`, which
+/// have to fit in the 100 characters that Discord allows.
+fn custom_id(action: &str, page: usize, owner: Option<UserId>, query: Query) -> String {
+    let query = match query {
+        Query::Daily { game, board } => format!("{}:day:{}", game.slug(), board),
+        Query::AllTime {
+            game,
+            include_today,
+            include_late,
+        } => format!(
+            "{}:all:{}:{}",
+            game.slug(),
+            include_today as u8,
+            include_late as u8
+        ),
+        Query::Windowed { game, window } => format!(
+            "{}:{}:{}:{}:{}:{}:{}",
+            game.slug(),
+            period_slug(window.period),
+            window.first_board,
+            window.last_board,
+            window.start,
+            window.end,
+            window.include_late as u8,
+        ),
+        Query::Overall {
+            period,
+            include_late,
+        } => format!("{}:{}:{}", OVERALL, period_slug(period), include_late as u8),
+        Query::Percentile {
+            game,
+            period,
+            include_late,
+        } => format!(
+            "{}:{}:{}:{}",
+            PERCENTILE,
+            game.map_or(ALL_GAMES, |game| game.slug()),
+            period_slug(period),
+            include_late as u8
+        ),
+        Query::Rating { game } => format!("{}:rating", game.slug()),
+    };
+
+    let owner = owner.map_or(NO_OWNER.to_owned(), |owner| owner.to_string());
+
+    format!("{}:{}:{}:{}:{}", NAME, action, page, owner, query)
+}
+
+/// Stands in for the owner in the custom IDs of leaderboards that nobody asked for.
+const NO_OWNER: &str = "-";
+
+/// Stands in for the game in the custom IDs of overall leaderboards.
+const OVERALL: &str = "overall";
+
+/// Stands in for the game in the custom IDs of percentile leaderboards, which are followed by the
+/// game they're for or [`ALL_GAMES`].
+const PERCENTILE: &str = "percentile";
+const ALL_GAMES: &str = "all";
+
+fn period_slug(period: Period) -> &'static str {
+    match period {
+        Period::Week => "week",
+        Period::Month => "month",
+        Period::Range => "range",
+        Period::AllTime => "all_time",
+    }
+}
+
+fn parse_period(slug: &str) -> Option<Period> {
+    match slug {
+        "week" => Some(Period::Week),
+        "month" => Some(Period::Month),
+        "range" => Some(Period::Range),
+        "all_time" => Some(Period::AllTime),
+        _ => None,
+    }
+}
+
+fn parse_custom_id(custom_id: &str) -> Option<(&str, usize, Option<UserId>, Query)> {
+    let mut parts = custom_id.strip_prefix(NAME)?.strip_prefix(':')?.split(':');
+    let mut next = || parts.next();
+
+    let action = next()?;
+    let page = next()?.parse().ok()?;
+    let owner = match next()? {
+        NO_OWNER => None,
+        owner => Some(UserId::new(owner.parse::<NonZeroU64>().ok()?.get())),
+    };
+    let flag = |part: &str| match part {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    };
+
+    let query = match next()? {
+        OVERALL => Query::Overall {
+            period: parse_period(next()?)?,
+            include_late: flag(next()?)?,
+        },
+        PERCENTILE => Query::Percentile {
+            game: match next()? {
+                ALL_GAMES => None,
+                slug => Some(game::by_slug(slug)?),
+            },
+            period: parse_period(next()?)?,
+            include_late: flag(next()?)?,
+        },
+        slug => {
+            let game = game::by_slug(slug)?;
+            match next()? {
+                "day" => Query::Daily {
+                    game,
+                    board: next()?.parse().ok()?,
+                },
+                "all" => Query::AllTime {
+                    game,
+                    include_today: flag(next()?)?,
+                    include_late: flag(next()?)?,
+                },
+                "rating" => Query::Rating { game },
+                period => Query::Windowed {
+                    game,
+                    window: Window {
+                        period: parse_period(period)?,
+                        first_board: next()?.parse().ok()?,
+                        last_board: next()?.parse().ok()?,
+                        start: next()?.parse().ok()?,
+                        end: next()?.parse().ok()?,
+                        include_late: flag(next()?)?,
+                    },
+                },
+            }
+        }
+    };
+
+    next().is_none().then_some((action, page, owner, query))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use serenity::all::UserId;
+
+    use super::{custom_id, edits_in_place, parse_custom_id, Query};
+    use crate::game::{
+        self,
+        leaderboards::{Period, Window},
+    };
+
+    fn round_trip(query: Query) {
+        for owner in [None, Some(UserId::new(u64::MAX))] {
+            let id = custom_id("next", 3, owner, query);
+            assert!(id.len() <= 100, "{} is too long", id);
+
+            let (action, page, parsed_owner, parsed) = parse_custom_id(&id).unwrap();
+            assert_eq!(action, "next");
+            assert_eq!(page, 3);
+            assert_eq!(parsed_owner, owner);
+            assert_eq!(custom_id("next", 3, parsed_owner, parsed), id);
+        }
+    }
+
+    #[test]
+    fn queries_round_trip_through_custom_ids() {
+        let game = game::by_slug("foodguessr").unwrap();
+
+        round_trip(Query::Daily {
+            game,
+            board: 20_000,
+        });
+        round_trip(Query::AllTime {
+            game,
+            include_today: true,
+            include_late: false,
+        });
+        round_trip(Query::Windowed {
+            game,
+            window: Window {
+                period: Period::Range,
+                first_board: 19_000,
+                last_board: 20_000,
+                start: NaiveDate::from_ymd_opt(2022, 1, 9).unwrap(),
+                end: NaiveDate::from_ymd_opt(2024, 10, 17).unwrap(),
+                include_late: true,
+            },
+        });
+        round_trip(Query::Overall {
+            period: Period::AllTime,
+            include_late: true,
+        });
+        round_trip(Query::Percentile {
+            game: None,
+            period: Period::Week,
+            include_late: false,
+        });
+        round_trip(Query::Percentile {
+            game: Some(game),
+            period: Period::Month,
+            include_late: true,
+        });
+        round_trip(Query::Rating { game });
+    }
+
+    #[test]
+    fn only_the_owner_turns_pages() {
+        let (owner, other) = (UserId::new(1), UserId::new(2));
+
+        assert!(edits_in_place(Some(owner), owner));
+        assert!(!edits_in_place(Some(owner), other));
+        assert!(!edits_in_place(None, owner));
+    }
+
+    #[test]
+    fn other_custom_ids_are_not_parsed() {
+        assert!(parse_custom_id("scores:flagle:1234").is_none());
+        assert!(parse_custom_id("leaderboard:next:0:-:flagle:day:1:extra").is_none());
+        assert!(parse_custom_id("leaderboard:next:0:-:nonsense:day:1").is_none());
+        assert!(parse_custom_id("leaderboard:next:0:-:overall:range").is_none());
+        assert!(parse_custom_id("leaderboard:next:0:0:flagle:day:1").is_none());
+        assert!(parse_custom_id("leaderboard:next:0:flagle:day:1").is_none());
+    }
+}
@@ -0,0 +1,97 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateCommand, CreateCommandOption,
+    CreateInteractionResponseMessage, GuildId, Http, ResolvedOption, ResolvedValue,
+};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+use super::{board_option, date_option, describe_board, find_board, find_string, game_option};
+use crate::{
+    game::{self, rating},
+    reactions,
+    settings::GuildSettings,
+};
+
+pub const NAME: &str = "score";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .description("Manage your own scores")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "withdraw",
+                "Withdraw a score you submitted by mistake",
+            )
+            .add_sub_option(game_option("The game the score is for"))
+            .add_sub_option(board_option("The number of the board the score is for"))
+            .add_sub_option(date_option(
+                "The date of the board the score is for, as YYYY-MM-DD",
+            )),
+        )
+}
+
+pub async fn run(
+    command: &CommandInteraction,
+    http: &Http,
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    settings: &GuildSettings,
+) -> CreateInteractionResponseMessage {
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        name: "withdraw",
+        value: ResolvedValue::SubCommand(options),
+        ..
+    }) = options.first()
+    else {
+        return CreateInteractionResponseMessage::new().content("An unexpected error occurred.");
+    };
+
+    let Some(game) = find_string(options, "game").and_then(game::by_slug) else {
+        warn!("cannot respond to command without a valid game parameter");
+        return CreateInteractionResponseMessage::new()
+            .content("You must specify a game to withdraw a score for!");
+    };
+
+    let board = match find_board(game, options) {
+        Ok(Some(board)) => board,
+        Ok(None) => {
+            return CreateInteractionResponseMessage::new()
+                .content("You must specify a board or a date!");
+        }
+        Err(content) => return CreateInteractionResponseMessage::new().content(content),
+    };
+
+    let user_id = command.user.id;
+    match game.remove_score(db_pool, guild_id, user_id, board).await {
+        Ok(Some(withdrawn)) => {
+            info!(%guild_id, %user_id, game = game.slug(), board, "withdrew score");
+
+            if settings.reactions_enabled {
+                reactions::withdraw(http, db_pool, game, guild_id, board, withdrawn).await;
+            }
+            rating::board_changed(db_pool, guild_id, game, board).await;
+
+            CreateInteractionResponseMessage::new()
+                .content(format!(
+                    "Withdrew your {} score for {}.",
+                    game.display_name(),
+                    describe_board(game, board),
+                ))
+                .ephemeral(true)
+        }
+        Ok(None) => CreateInteractionResponseMessage::new()
+            .content(format!(
+                "You don't have a {} score for {}!",
+                game.display_name(),
+                describe_board(game, board),
+            ))
+            .ephemeral(true),
+        Err(error) => {
+            error!(%error, "failed to withdraw score");
+            CreateInteractionResponseMessage::new().content("An unexpected error occurred.")
+        }
+    }
+}
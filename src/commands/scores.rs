@@ -0,0 +1,381 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serenity::all::{
+    ActionRowComponent, CommandInteraction, CommandOptionType, Context, CreateActionRow,
+    CreateAllowedMentions, CreateCommand, CreateCommandOption, CreateInputText,
+    CreateInteractionResponse, CreateInteractionResponseMessage, CreateModal, GuildId, Http,
+    InputTextStyle, Mention, ModalInteraction, Permissions, ResolvedOption, ResolvedValue, UserId,
+};
+use sqlx::PgPool;
+use tracing::{error, info, instrument, warn};
+
+use super::{
+    board_option, can_manage_guild, date_option, describe_board, find_board, find_string,
+    find_user, game_option, parse_date,
+};
+use crate::{
+    game::{
+        self, rating, DynGame, RecordScoreError, ScoreCorrectionError, ScoreInsertionError,
+        Submission,
+    },
+    reactions,
+    settings::GuildSettings,
+};
+
+pub const NAME: &str = "scores";
+
+/// The `custom_id` of the text input in the modal that scores are entered in.
+const SCORE_INPUT: &str = "score";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .description("Correct the scores recorded in this server")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "remove",
+                "Delete a user's score for a board",
+            )
+            .add_sub_option(game_option("The game the score is for"))
+            .add_sub_option(user_option(
+                "user",
+                "The user whose score should be deleted",
+            ))
+            .add_sub_option(board_option("The number of the board the score is for"))
+            .add_sub_option(date_option(
+                "The date of the board the score is for, as YYYY-MM-DD",
+            )),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "reassign",
+                "Move a score to a different user, e.g. if it was posted on their behalf",
+            )
+            .add_sub_option(game_option("The game the score is for"))
+            .add_sub_option(user_option("from", "The user the score is recorded for"))
+            .add_sub_option(user_option("to", "The user the score should belong to"))
+            .add_sub_option(board_option("The number of the board the score is for"))
+            .add_sub_option(date_option(
+                "The date of the board the score is for, as YYYY-MM-DD",
+            )),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "enter",
+                "Enter a score for a user by pasting it in",
+            )
+            .add_sub_option(game_option("The game the score is for"))
+            .add_sub_option(user_option("user", "The user the score belongs to"))
+            .add_sub_option(date_option(
+                "The date the score was originally posted, if not today, as YYYY-MM-DD",
+            )),
+        )
+}
+
+fn user_option(name: &str, description: &str) -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::User, name, description).required(true)
+}
+
+/// Entering a score responds with a modal rather than a message, since shared results span
+/// several lines and command options can't, so this command sends its own response.
+#[instrument(skip_all)]
+pub async fn run(ctx: &Context, command: &CommandInteraction, db_pool: &PgPool) {
+    let response = match command.guild_id {
+        Some(guild_id) => process(command, &ctx.http, db_pool, guild_id).await,
+        None => {
+            warn!("cannot continue processing interaction without guild ID");
+            message("This command can only be run in a server!")
+        }
+    };
+
+    match command.create_response(&ctx.http, response).await {
+        Ok(_) => info!("responded to command"),
+        Err(error) => error!(%error, "failed to respond to command"),
+    }
+}
+
+async fn process(
+    command: &CommandInteraction,
+    http: &Http,
+    db_pool: &PgPool,
+    guild_id: GuildId,
+) -> CreateInteractionResponse {
+    if !can_manage_guild(command.member.as_deref()) {
+        return message("You need the Manage Server permission to correct scores!");
+    }
+
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        name,
+        value: ResolvedValue::SubCommand(options),
+        ..
+    }) = options.first()
+    else {
+        return message("An unexpected error occurred.");
+    };
+
+    let Some(game) = find_string(options, "game").and_then(game::by_slug) else {
+        warn!("cannot respond to command without a valid game parameter");
+        return message("You must specify a game!");
+    };
+
+    if *name == "enter" {
+        let Some(user) = find_user(options, "user") else {
+            return message("You must specify a user!");
+        };
+
+        let posted_on = match find_string(options, "date").map(parse_date).transpose() {
+            Ok(Some(date)) if date > game.today() => {
+                return message("That date hasn't happened yet!");
+            }
+            Ok(posted_on) => posted_on,
+            Err(content) => return message(content),
+        };
+
+        return enter_modal(game, user.id, posted_on);
+    }
+
+    let board = match find_board(game, options) {
+        Ok(Some(board)) => board,
+        Ok(None) => return message("You must specify a board or a date!"),
+        Err(content) => return message(content),
+    };
+
+    let result = match *name {
+        "remove" => {
+            let Some(user) = find_user(options, "user") else {
+                return message("You must specify a user!");
+            };
+
+            let settings = GuildSettings::get(db_pool, guild_id)
+                .await
+                .unwrap_or_else(|error| {
+                    error!(%error, "failed to fetch guild settings, falling back to defaults");
+                    GuildSettings::default()
+                });
+
+            let removed = game.remove_score(db_pool, guild_id, user.id, board).await;
+            if let (Ok(Some(removed)), true) = (&removed, settings.reactions_enabled) {
+                reactions::withdraw(http, db_pool, game, guild_id, board, *removed).await;
+            }
+
+            removed.map(|removed| match removed {
+                Some(_) => format!(
+                    "Deleted {}'s {} score for {}.",
+                    Mention::User(user.id),
+                    game.display_name(),
+                    describe_board(game, board),
+                ),
+                None => format!(
+                    "{} doesn't have a {} score for {}!",
+                    Mention::User(user.id),
+                    game.display_name(),
+                    describe_board(game, board),
+                ),
+            })
+        }
+        "reassign" => {
+            let (Some(from), Some(to)) = (find_user(options, "from"), find_user(options, "to"))
+            else {
+                return message("You must specify who to move the score from and to!");
+            };
+
+            game.reassign_score(db_pool, guild_id, board, from.id, to)
+                .await
+                .map(|reassigned| {
+                    if reassigned {
+                        format!(
+                            "Moved {}'s {} score for {} to {}.",
+                            Mention::User(from.id),
+                            game.display_name(),
+                            describe_board(game, board),
+                            Mention::User(to.id),
+                        )
+                    } else {
+                        format!(
+                            "{} doesn't have a {} score for {}!",
+                            Mention::User(from.id),
+                            game.display_name(),
+                            describe_board(game, board),
+                        )
+                    }
+                })
+        }
+        _ => return message("An unexpected error occurred."),
+    };
+
+    match result {
+        Ok(content) => {
+            info!(%guild_id, game = game.slug(), board, "corrected score");
+            rating::board_changed(db_pool, guild_id, game, board).await;
+            message(content)
+        }
+        Err(ScoreCorrectionError::Conflict) => {
+            message("That user already has a score for that board! Remove it first.")
+        }
+        Err(error) => {
+            error!(%error, "failed to correct score");
+            message("An unexpected error occurred.")
+        }
+    }
+}
+
+/// Ask for the text of a score, to be handled by [`submit`] once it's filled in. The score will be
+/// recorded as posted on `posted_on`, or just now if that isn't given.
+fn enter_modal(
+    game: &dyn DynGame,
+    user_id: UserId,
+    posted_on: Option<NaiveDate>,
+) -> CreateInteractionResponse {
+    let input = CreateInputText::new(InputTextStyle::Paragraph, "Score", SCORE_INPUT)
+        .placeholder(format!("Paste the {} results here", game.display_name()));
+
+    let mut custom_id = format!("{}:{}:{}", NAME, game.slug(), user_id);
+    if let Some(posted_on) = posted_on {
+        custom_id = format!("{}:{}", custom_id, posted_on);
+    }
+
+    CreateInteractionResponse::Modal(
+        CreateModal::new(custom_id, format!("Enter a {} score", game.display_name()))
+            .components(vec![CreateActionRow::InputText(input)]),
+    )
+}
+
+/// Whether `modal` was opened by this command, and so should be handled by [`submit`].
+pub fn is_own_modal(modal: &ModalInteraction) -> bool {
+    modal
+        .data
+        .custom_id
+        .strip_prefix(NAME)
+        .is_some_and(|rest| rest.starts_with(':'))
+}
+
+/// Record the score that was entered into the modal opened by `/scores enter`, as if the user had
+/// posted it just now, or on the date given to the command.
+#[instrument(skip_all)]
+pub async fn submit(ctx: &Context, modal: &ModalInteraction, db_pool: &PgPool) {
+    let content = match record(ctx, modal, db_pool).await {
+        Ok(content) | Err(content) => content,
+    };
+
+    let response = CreateInteractionResponseMessage::new()
+        .content(content)
+        .allowed_mentions(CreateAllowedMentions::new());
+
+    match modal
+        .create_response(&ctx.http, CreateInteractionResponse::Message(response))
+        .await
+    {
+        Ok(_) => info!("responded to modal"),
+        Err(error) => error!(%error, "failed to respond to modal"),
+    }
+}
+
+async fn record(
+    ctx: &Context,
+    modal: &ModalInteraction,
+    db_pool: &PgPool,
+) -> Result<String, String> {
+    let Some(guild_id) = modal.guild_id else {
+        warn!("cannot continue processing interaction without guild ID");
+        return Err("Scores can only be entered in a server!".to_owned());
+    };
+
+    if !can_manage_guild(modal.member.as_ref()) {
+        return Err("You need the Manage Server permission to enter scores!".to_owned());
+    }
+
+    let mut parts = modal.data.custom_id.splitn(4, ':').skip(1);
+    let (Some(game), Some(user_id), Ok(posted_on)) = (
+        parts.next().and_then(game::by_slug),
+        parts.next().and_then(|id| id.parse::<UserId>().ok()),
+        parts.next().map(str::parse::<NaiveDate>).transpose(),
+    ) else {
+        warn!(custom_id = %modal.data.custom_id, "received modal with malformed custom ID");
+        return Err("An unexpected error occurred.".to_owned());
+    };
+
+    let text = modal
+        .data
+        .components
+        .iter()
+        .flat_map(|row| &row.components)
+        .find_map(|component| match component {
+            ActionRowComponent::InputText(input) if input.custom_id == SCORE_INPUT => {
+                input.value.as_deref()
+            }
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    let user = user_id.to_user(&ctx.http).await.map_err(|error| {
+        error!(%error, %user_id, "failed to fetch user");
+        "Couldn't find that user!".to_owned()
+    })?;
+
+    let submission = Submission {
+        message_id: None,
+        channel_id: modal.channel_id,
+        submitted_at: posted_on.map_or_else(Utc::now, |date| posted_at(game, date)),
+    };
+
+    match game
+        .record_score(db_pool, guild_id, &user, text, submission)
+        .await
+    {
+        Ok(board) => {
+            info!(%guild_id, %user_id, game = game.slug(), board, "entered score");
+            rating::board_changed(db_pool, guild_id, game, board).await;
+
+            // A score entered by hand has no message to react to, but it can still take the
+            // "best so far" reaction away from someone else
+            let settings = GuildSettings::get(db_pool, guild_id)
+                .await
+                .unwrap_or_else(|error| {
+                    error!(%error, "failed to fetch guild settings, falling back to defaults");
+                    GuildSettings::default()
+                });
+            if settings.reactions_enabled {
+                reactions::update_best(&ctx.http, db_pool, game, guild_id, board).await;
+            }
+
+            Ok(format!(
+                "Recorded {}'s {} score.",
+                Mention::User(user_id),
+                game.display_name(),
+            ))
+        }
+        Err(RecordScoreError::NotAScore(reason)) => Err(format!(
+            "That isn't a valid {} score: {}",
+            game.display_name(),
+            reason,
+        )),
+        Err(RecordScoreError::Insertion(ScoreInsertionError::Duplicate)) => Err(format!(
+            "{} already has a score for that {} board!",
+            Mention::User(user_id),
+            game.display_name(),
+        )),
+        Err(error) => {
+            error!(%error, "failed to record entered score");
+            Err("An unexpected error occurred.".to_owned())
+        }
+    }
+}
+
+/// A time on `date` as far as `game` is concerned, for a score that was posted then. This is the
+/// same time of day as now, so that it falls on the right day however the game splits its days,
+/// and the score is on time if its board is the one from that day.
+fn posted_at(game: &dyn DynGame, date: NaiveDate) -> DateTime<Utc> {
+    Utc::now() - (game.today() - date)
+}
+
+fn message(content: impl Into<String>) -> CreateInteractionResponse {
+    CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new()),
+    )
+}
@@ -0,0 +1,61 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, CreateAllowedMentions, CreateCommand,
+    CreateCommandOption, CreateInteractionResponseMessage, GuildId,
+};
+use sqlx::PgPool;
+use tracing::{error, warn};
+
+use super::{find_string, find_user, game_option};
+use crate::{game, settings::GuildSettings};
+
+pub const NAME: &str = "stats";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .description("View a player's stats for a game")
+        .add_option(game_option("The game to view stats for"))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::User,
+            "user",
+            "The player to view stats for (defaults to you)",
+        ))
+}
+
+pub async fn run(
+    command: &CommandInteraction,
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    settings: &GuildSettings,
+) -> CreateInteractionResponseMessage {
+    let options = command.data.options();
+
+    let Some(slug) = find_string(&options, "game") else {
+        warn!("cannot respond to command without a value for the game parameter");
+        return CreateInteractionResponseMessage::new()
+            .content("You must specify a game in order to view stats!");
+    };
+
+    let Some(game) = game::by_slug(slug) else {
+        return CreateInteractionResponseMessage::new()
+            .content(format!("Unknown game \"{}\"!", slug));
+    };
+
+    if !settings.game_enabled(game.slug()) {
+        return CreateInteractionResponseMessage::new().content(format!(
+            "{} is disabled in this server!",
+            game.display_name()
+        ));
+    }
+
+    let user = find_user(&options, "user").unwrap_or(&command.user);
+
+    match game.stats(db_pool, guild_id, user.id).await {
+        Ok(embed) => CreateInteractionResponseMessage::new()
+            .embed(embed)
+            .allowed_mentions(CreateAllowedMentions::new()),
+        Err(error) => {
+            error!(%error, "failed to calculate stats");
+            CreateInteractionResponseMessage::new().content("An unexpected error occurred.")
+        }
+    }
+}
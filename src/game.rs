@@ -1,32 +1,666 @@
-use std::{fmt, str::FromStr};
+use std::{fmt, future::Future, marker::PhantomData, str::FromStr};
 
-use serenity::all::{CreateEmbed, GuildId, User};
-use sqlx::{Error as SqlxError, PgPool};
+use chrono::{DateTime, NaiveDate, Utc};
+use indoc::formatdoc;
+use serenity::{
+    all::{ChannelId, Context, CreateEmbed, GuildId, Message, MessageId, User, UserId},
+    async_trait,
+};
+use sqlx::{postgres::PgRow, Error as SqlxError, FromRow, PgPool, Row as _};
 use thiserror::Error;
+use tracing::{debug, error};
 
-use crate::persist::{GuildUserInsertionError, InsertionTarget};
+use crate::{
+    game::{
+        connections::Connections,
+        flagle::Flagle,
+        foodguessr::FoodGuessr,
+        geogrid::GeoGrid,
+        globle::Globle,
+        leaderboards::{Listing, MedalsEntry, Window, Windowed},
+        stats::Stats,
+        travle::Travle,
+        wordle::Wordle,
+        worldle::Worldle,
+    },
+    persist::{self, GuildUserInsertionError, InsertionTarget},
+    settings::GuildSettings,
+    Accepted, Bot, Rejected,
+};
 
+pub mod connections;
 pub mod flagle;
 pub mod foodguessr;
 pub mod geogrid;
+pub mod globle;
+pub mod leaderboards;
+pub mod percentile;
+pub mod rating;
+pub mod stats;
+pub mod travle;
+pub mod wordle;
+pub mod worldle;
+
+/// Every game the bot knows about, in the order that score detection is attempted and that games
+/// are listed in slash command choices.
+pub static GAMES: &[&dyn DynGame] = &[
+    &Registered::<GeoGrid>::new(),
+    &Registered::<Flagle>::new(),
+    &Registered::<FoodGuessr>::new(),
+    &Registered::<Wordle>::new(),
+    &Registered::<Connections>::new(),
+    &Registered::<Worldle>::new(),
+    &Registered::<Globle>::new(),
+    &Registered::<Travle>::new(),
+];
+
+/// Look up a registered game by its slug.
+pub fn by_slug(slug: &str) -> Option<&'static dyn DynGame> {
+    GAMES.iter().copied().find(|game| game.slug() == slug)
+}
 
-pub trait Game {
+pub trait Game: Send + Sync + 'static {
     type Score: Score<Game = Self>;
+    type DailyLeaderboard: Into<Listing> + fmt::Debug + Send;
+    type AllTimeLeaderboard: Into<Listing> + fmt::Debug + Send;
+
+    /// A stable, machine-readable identifier for this game, e.g. "geogrid". This is used as the
+    /// value of slash command choices, so it must never change once a game has been released.
+    fn slug() -> &'static str;
+
+    /// A human-readable name for this game, e.g. "GeoGrid".
+    fn display_name() -> &'static str;
+
+    /// How this game's scores are stored, for queries that work the same way across every game.
+    const SCORE_TABLE: ScoreTable;
+
+    /// Whether players know this game's boards by number. If not, boards can only be looked up by
+    /// date, and the numbers given by [`ScoreTable::board`] are an implementation detail.
+    const NUMBERED_BOARDS: bool = true;
+
+    /// The number of the board that is active right now, as given by [`ScoreTable::board`].
+    fn board_now() -> usize;
+
+    /// Today's date as far as this game is concerned, i.e. the date of [`Game::board_now`], which
+    /// might not be the date in UTC.
+    fn today() -> NaiveDate;
+
+    /// The number of the board that occurred on `date`, as given by [`ScoreTable::board`]. Returns
+    /// `None` if the game hadn't started yet on that date.
+    fn board_on_date(date: NaiveDate) -> Option<usize>;
+
+    fn daily_leaderboard(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        board: usize,
+    ) -> impl Future<Output = Result<Self::DailyLeaderboard, CalculateDailyError>> + Send;
+
+    fn all_time_leaderboard(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        include_today: bool,
+        include_late: bool,
+    ) -> impl Future<Output = Result<Self::AllTimeLeaderboard, CalculateAllTimeError>> + Send;
+}
+
+/// A description of the table that a [`Game`]'s scores are stored in. Each expression is an SQL
+/// fragment that refers to a row of the table under the alias `s`.
+#[derive(Clone, Copy, Debug)]
+pub struct ScoreTable {
+    /// The name of the table, e.g. "flagle_scores".
+    pub name: &'static str,
+
+    /// An integer expression for the board that a score is for. Consecutive days must have
+    /// consecutive board numbers.
+    pub board: &'static str,
+
+    /// A boolean expression for whether a score was submitted on the day of its board.
+    pub on_time: &'static str,
+
+    /// A numeric expression for the value of a score.
+    pub score: &'static str,
+
+    /// A boolean expression for whether a score should be ranked at all, e.g. to leave out
+    /// unsolved boards.
+    pub ranked: &'static str,
+
+    /// Whether higher values of [`ScoreTable::score`] are better.
+    pub higher_is_better: bool,
+
+    /// Whether guilds can choose to break ties by who submitted first (see
+    /// [`GuildSettings::flagle_tiebreak_by_time`]), rather than letting tied scores share a place.
+    pub time_tiebreak: bool,
+}
+
+impl ScoreTable {
+    /// Whether tied scores are ordered by who submitted first in a guild with `settings`. Scores
+    /// from before submission times were recorded have no time to compare, so they're placed
+    /// after any timed score with the same value, and only share a place with each other.
+    pub fn breaks_ties_by_time(&self, settings: &GuildSettings) -> bool {
+        self.time_tiebreak && settings.flagle_tiebreak_by_time
+    }
+
+    /// An `ORDER BY` expression that sorts scores from best to worst in a guild with `settings`.
+    /// Every query that ranks scores uses this, so that places and medals agree everywhere.
+    pub fn order_by(&self, settings: &GuildSettings) -> String {
+        format!(
+            "{} {}{}",
+            self.score,
+            if self.higher_is_better { "DESC" } else { "ASC" },
+            if self.breaks_ties_by_time(settings) {
+                ", s.submitted_at ASC NULLS LAST"
+            } else {
+                ""
+            },
+        )
+    }
+}
+
+/// An object-safe view of a [`Game`], so that games can be stored together in [`GAMES`] and
+/// dispatched to by slug.
+#[async_trait]
+pub trait DynGame: Send + Sync {
+    fn slug(&self) -> &'static str;
+
+    fn display_name(&self) -> &'static str;
+
+    fn score_table(&self) -> ScoreTable;
+
+    fn numbered_boards(&self) -> bool;
+
+    fn board_now(&self) -> usize;
+
+    fn today(&self) -> NaiveDate;
+
+    fn board_on_date(&self, date: NaiveDate) -> Option<usize>;
+
+    /// Try to parse `msg` as a score for this game, and process it as posted at `submission` if
+    /// successful. Returns how the score was accepted or why it was rejected, e.g. because the game
+    /// is disabled or scores aren't recorded in the message's channel, or `None` if the message
+    /// isn't a score for this game.
+    async fn detect_score(
+        &self,
+        bot: &Bot,
+        ctx: &Context,
+        msg: &Message,
+        guild_id: GuildId,
+        submission: Submission,
+    ) -> Option<Result<Accepted, Rejected>>;
+
+    /// Whether `content` can be parsed as a score for this game, without recording it.
+    fn is_score(&self, content: &str) -> bool;
+
+    /// Try to parse `content` as a score for this game, and record it for `user` without reacting
+    /// or replying if successful. Returns the recorded score's board.
+    async fn record_score(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        user: &User,
+        content: &str,
+        submission: Submission,
+    ) -> Result<usize, RecordScoreError>;
+
+    async fn daily_leaderboard(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        board: usize,
+    ) -> Result<Listing, CalculateDailyError>;
+
+    async fn all_time_leaderboard(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        include_today: bool,
+        include_late: bool,
+    ) -> Result<Listing, CalculateAllTimeError>;
+
+    async fn windowed_leaderboard(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        window: Window,
+    ) -> Result<Listing, CalculateAllTimeError>;
+
+    /// The medals won by each player over `window`, ranked by the guild's ranking strategy.
+    async fn windowed_medals(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        window: Window,
+    ) -> Result<Vec<(UserId, MedalsEntry)>, CalculateAllTimeError>;
+
+    /// Each player's percentile on each board in `window`, as given by
+    /// [`percentile::board_percentiles`].
+    async fn board_percentiles(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        window: Window,
+    ) -> Result<Vec<(UserId, f64)>, CalculateAllTimeError>;
+
+    async fn stats(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<CreateEmbed, CalculateStatsError>;
+
+    /// Whether `content` is the same score as the one that was recorded from the message with
+    /// `message_id`, e.g. because an edit to the message didn't change the score.
+    async fn is_unchanged(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        message_id: MessageId,
+        content: &str,
+    ) -> bool;
+
+    /// Delete the score that was recorded from the message with `message_id`, if there is one.
+    /// Returns the board of the deleted score.
+    async fn retract_score(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        message_id: MessageId,
+    ) -> Result<Option<usize>, ScoreRetractionError>;
+
+    /// Delete `user_id`'s score for `board`, if they have one. Returns where the deleted score was
+    /// submitted.
+    async fn remove_score(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        user_id: UserId,
+        board: usize,
+    ) -> Result<Option<ScoreMessage>, ScoreCorrectionError>;
+
+    /// Find the messages holding the best on-time score for `board`, if there are any. Tied scores
+    /// all count as the best, unless the guild breaks ties by time (see
+    /// [`ScoreTable::breaks_ties_by_time`]).
+    async fn best_scores(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        board: usize,
+    ) -> Result<Vec<ScoreMessage>, CalculateDailyError>;
+
+    /// Move `from`'s score for `board` to `to`, e.g. because it was posted on their behalf.
+    /// Returns whether there was a score to move.
+    async fn reassign_score(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        board: usize,
+        from: UserId,
+        to: &User,
+    ) -> Result<bool, ScoreCorrectionError>;
+}
+
+pub struct Registered<G>(PhantomData<fn() -> G>);
+
+impl<G> Registered<G> {
+    pub(crate) const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[async_trait]
+impl<G> DynGame for Registered<G>
+where
+    G: Game,
+{
+    fn slug(&self) -> &'static str {
+        G::slug()
+    }
+
+    fn display_name(&self) -> &'static str {
+        G::display_name()
+    }
+
+    fn score_table(&self) -> ScoreTable {
+        G::SCORE_TABLE
+    }
+
+    fn numbered_boards(&self) -> bool {
+        G::NUMBERED_BOARDS
+    }
+
+    fn board_now(&self) -> usize {
+        G::board_now()
+    }
+
+    fn today(&self) -> NaiveDate {
+        G::today()
+    }
+
+    fn board_on_date(&self, date: NaiveDate) -> Option<usize> {
+        G::board_on_date(date)
+    }
+
+    async fn detect_score(
+        &self,
+        bot: &Bot,
+        ctx: &Context,
+        msg: &Message,
+        guild_id: GuildId,
+        submission: Submission,
+    ) -> Option<Result<Accepted, Rejected>> {
+        match msg.content.parse::<G::Score>() {
+            Ok(score) => Some(
+                bot.process_score::<G>(score, ctx, msg, guild_id, submission)
+                    .await,
+            ),
+            Err(error) => {
+                debug!(reason = %error, "message isn't a {} score", G::display_name());
+                None
+            }
+        }
+    }
+
+    fn is_score(&self, content: &str) -> bool {
+        content.parse::<G::Score>().is_ok()
+    }
 
-    /// A human-readable description of this game, e.g. "Geogrid".
-    fn description() -> &'static str;
+    async fn record_score(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        user: &User,
+        content: &str,
+        submission: Submission,
+    ) -> Result<usize, RecordScoreError> {
+        let score = content
+            .parse::<G::Score>()
+            .map_err(|error| RecordScoreError::NotAScore(error.to_string()))?;
+
+        let inserted = score.insert(db_pool, guild_id, user, submission).await?;
+
+        Ok(inserted.board())
+    }
 
     async fn daily_leaderboard(
+        &self,
         db_pool: &PgPool,
         guild_id: GuildId,
-    ) -> Result<impl Into<CreateEmbed> + fmt::Debug, CalculateDailyError>;
+        board: usize,
+    ) -> Result<Listing, CalculateDailyError> {
+        G::daily_leaderboard(db_pool, guild_id, board)
+            .await
+            .map(Into::into)
+    }
 
     async fn all_time_leaderboard(
+        &self,
         db_pool: &PgPool,
         guild_id: GuildId,
         include_today: bool,
         include_late: bool,
-    ) -> Result<impl Into<CreateEmbed> + fmt::Debug, CalculateAllTimeError>;
+    ) -> Result<Listing, CalculateAllTimeError> {
+        G::all_time_leaderboard(db_pool, guild_id, include_today, include_late)
+            .await
+            .map(Into::into)
+    }
+
+    async fn windowed_leaderboard(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        window: Window,
+    ) -> Result<Listing, CalculateAllTimeError> {
+        Windowed::calculate::<G>(db_pool, guild_id, window)
+            .await
+            .map(Into::into)
+    }
+
+    async fn windowed_medals(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        window: Window,
+    ) -> Result<Vec<(UserId, MedalsEntry)>, CalculateAllTimeError> {
+        Windowed::calculate::<G>(db_pool, guild_id, window)
+            .await
+            .map(|windowed| windowed.medals_listing)
+    }
+
+    async fn board_percentiles(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        window: Window,
+    ) -> Result<Vec<(UserId, f64)>, CalculateAllTimeError> {
+        percentile::board_percentiles::<G>(db_pool, guild_id, window).await
+    }
+
+    async fn stats(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<CreateEmbed, CalculateStatsError> {
+        Stats::calculate::<G>(db_pool, guild_id, user_id)
+            .await
+            .map(Into::into)
+    }
+
+    async fn is_unchanged(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        message_id: MessageId,
+        content: &str,
+    ) -> bool {
+        let Ok(score) = content.parse::<G::Score>() else {
+            return false;
+        };
+
+        let get_score_string = formatdoc!(
+            "
+            SELECT *
+            FROM {name} s
+            WHERE
+                s.guild_id = $1
+                AND s.message_id = $2;
+            ",
+            name = G::SCORE_TABLE.name,
+        );
+        match sqlx::query(get_score_string.as_ref())
+            .bind(guild_id.get() as i64)
+            .bind(message_id.get() as i64)
+            .fetch_optional(db_pool)
+            .await
+            .and_then(|row| row.map(|row| score.is_recorded_in(&row)).transpose())
+        {
+            Ok(unchanged) => unchanged.unwrap_or(false),
+            Err(error) => {
+                error!(%error, "failed to compare score with the recorded one");
+                false
+            }
+        }
+    }
+
+    async fn retract_score(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        message_id: MessageId,
+    ) -> Result<Option<usize>, ScoreRetractionError> {
+        let delete_score_string = formatdoc!(
+            "
+            DELETE FROM {name} s
+            WHERE
+                s.guild_id = $1
+                AND s.message_id = $2
+            RETURNING {board};
+            ",
+            name = G::SCORE_TABLE.name,
+            board = G::SCORE_TABLE.board,
+        );
+        let delete_score = sqlx::query(delete_score_string.as_ref());
+        match delete_score
+            .bind(guild_id.get() as i64)
+            .bind(message_id.get() as i64)
+            .fetch_optional(db_pool)
+            .await
+            .and_then(|row| row.map(|row| row.try_get::<i32, _>(0)).transpose())
+        {
+            Ok(board) => Ok(board.map(|board| board as usize)),
+            Err(error) => {
+                error!(%error, "failed to delete score");
+                Err(ScoreRetractionError::Unexpected(error))
+            }
+        }
+    }
+
+    async fn remove_score(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        user_id: UserId,
+        board: usize,
+    ) -> Result<Option<ScoreMessage>, ScoreCorrectionError> {
+        let delete_score_string = formatdoc!(
+            "
+            DELETE FROM {name} s
+            WHERE
+                s.guild_id = $1
+                AND s.user_id = $2
+                AND {board} = $3
+            RETURNING
+                s.message_id,
+                s.channel_id;
+            ",
+            name = G::SCORE_TABLE.name,
+            board = G::SCORE_TABLE.board,
+        );
+        let delete_score = sqlx::query(delete_score_string.as_ref());
+        let row = match delete_score
+            .bind(guild_id.get() as i64)
+            .bind(user_id.get() as i64)
+            .bind(board as i32)
+            .fetch_optional(db_pool)
+            .await
+        {
+            Ok(row) => row,
+            Err(error) => {
+                error!(%error, "failed to delete score");
+                return Err(ScoreCorrectionError::Unexpected(error));
+            }
+        };
+
+        row.map(|row| ScoreMessageRow::from_row(&row))
+            .transpose()
+            .map(|row| row.map(Into::into))
+            .map_err(ScoreCorrectionError::FromRow)
+    }
+
+    async fn best_scores(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        board: usize,
+    ) -> Result<Vec<ScoreMessage>, CalculateDailyError> {
+        let settings = GuildSettings::get(db_pool, guild_id)
+            .await
+            .unwrap_or_else(|error| {
+                error!(%error, "failed to fetch guild settings, falling back to defaults");
+                GuildSettings::default()
+            });
+
+        let get_best_scores_string = formatdoc!(
+            "
+            WITH cte AS (
+                SELECT
+                    s.message_id,
+                    s.channel_id,
+                    RANK() OVER (ORDER BY {order}) AS place
+                FROM {name} s
+                WHERE
+                    s.guild_id = $1
+                    AND {board} = $2
+                    AND ({on_time})
+                    AND ({ranked})
+            )
+            SELECT
+                message_id,
+                channel_id
+            FROM cte
+            WHERE place = 1;
+            ",
+            name = G::SCORE_TABLE.name,
+            board = G::SCORE_TABLE.board,
+            on_time = G::SCORE_TABLE.on_time,
+            ranked = G::SCORE_TABLE.ranked,
+            order = G::SCORE_TABLE.order_by(&settings),
+        );
+        let get_best_scores = sqlx::query(get_best_scores_string.as_ref());
+        let rows = get_best_scores
+            .bind(guild_id.get() as i64)
+            .bind(board as i32)
+            .fetch_all(db_pool)
+            .await
+            .map_err(CalculateDailyError::Unexpected)?;
+
+        rows.iter()
+            .map(|row| ScoreMessageRow::from_row(row).map(Into::into))
+            .collect::<Result<_, _>>()
+            .map_err(CalculateDailyError::FromRow)
+    }
+
+    async fn reassign_score(
+        &self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        board: usize,
+        from: UserId,
+        to: &User,
+    ) -> Result<bool, ScoreCorrectionError> {
+        let mut txn = db_pool
+            .begin()
+            .await
+            .map_err(ScoreCorrectionError::BeginTxn)?;
+
+        persist::insert_guild_user(&mut txn, guild_id, to).await?;
+
+        let update_score_string = formatdoc!(
+            "
+            UPDATE {name} s
+            SET user_id = $4
+            WHERE
+                s.guild_id = $1
+                AND s.user_id = $2
+                AND {board} = $3;
+            ",
+            name = G::SCORE_TABLE.name,
+            board = G::SCORE_TABLE.board,
+        );
+        let update_score = sqlx::query(update_score_string.as_ref());
+        let reassigned = match update_score
+            .bind(guild_id.get() as i64)
+            .bind(from.get() as i64)
+            .bind(board as i32)
+            .bind(to.id.get() as i64)
+            .execute(txn.as_mut())
+            .await
+        {
+            Ok(result) => result.rows_affected() > 0,
+            Err(SqlxError::Database(error)) if error.is_unique_violation() => {
+                return Err(ScoreCorrectionError::Conflict);
+            }
+            Err(error) => {
+                error!(%error, "failed to reassign score");
+                return Err(ScoreCorrectionError::Unexpected(error));
+            }
+        };
+
+        txn.commit()
+            .await
+            .map_err(ScoreCorrectionError::CommitTxn)?;
+
+        Ok(reassigned)
+    }
 }
 
 #[derive(Debug, Error)]
@@ -58,15 +692,62 @@ pub enum CalculateAllTimeError {
     Todo,
 }
 
-pub trait Score: FromStr + fmt::Debug {
+#[derive(Debug, Error)]
+pub enum CalculateStatsError {
+    #[error("failed to extract data from row: {0}")]
+    FromRow(#[source] SqlxError),
+
+    #[error("unexpected SQLx error: {0}")]
+    Unexpected(SqlxError),
+}
+
+pub trait Score: FromStr<Err: fmt::Display + Send> + fmt::Debug + Send {
     type Game: Game;
 
-    async fn insert(
+    /// Whether `row`, from [`Game::SCORE_TABLE`], records this score, whenever and wherever it was
+    /// submitted.
+    fn is_recorded_in(&self, row: &PgRow) -> Result<bool, SqlxError>;
+
+    fn insert(
         self,
         db_pool: &PgPool,
         guild_id: GuildId,
         user: &User,
-    ) -> Result<impl InsertedScore, ScoreInsertionError>;
+        submission: Submission,
+    ) -> impl Future<Output = Result<impl InsertedScore + Send, ScoreInsertionError>> + Send;
+}
+
+/// Where and when a score was posted.
+#[derive(Clone, Copy, Debug)]
+pub struct Submission {
+    /// The message the score was posted in, or `None` if it was entered by hand.
+    pub message_id: Option<MessageId>,
+    pub channel_id: ChannelId,
+
+    /// When the message was originally sent, which decides whether the score is on time. This is
+    /// not when the bot processed it, which could be much later if the bot was offline.
+    pub submitted_at: DateTime<Utc>,
+}
+
+impl Submission {
+    /// Where and when `msg` was last edited. An edit can change a score completely, so an edited
+    /// score is only on time if the edit was.
+    pub fn edited(msg: &Message) -> Self {
+        Self {
+            submitted_at: *msg.edited_timestamp.unwrap_or(msg.timestamp),
+            ..msg.into()
+        }
+    }
+}
+
+impl From<&Message> for Submission {
+    fn from(msg: &Message) -> Self {
+        Self {
+            message_id: Some(msg.id),
+            channel_id: msg.channel_id,
+            submitted_at: *msg.timestamp,
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -74,6 +755,9 @@ pub enum ScoreInsertionError {
     #[error("score is a duplicate entry for its board number, user and guild")]
     Duplicate,
 
+    #[error("score was submitted before the game's first board")]
+    BeforeFirstBoard,
+
     #[error("failed to begin transaction: {0}")]
     BeginTxn(#[source] SqlxError),
 
@@ -95,7 +779,92 @@ pub enum ScoreInsertionError {
     GuildUserInsertion(#[from] GuildUserInsertionError),
 }
 
+#[derive(Debug, Error)]
+pub enum ScoreRetractionError {
+    #[error("unexpected SQLx error: {0}")]
+    Unexpected(SqlxError),
+}
+
+#[derive(Debug, Error)]
+pub enum RecordScoreError {
+    #[error("not a valid score: {0}")]
+    NotAScore(String),
+
+    #[error(transparent)]
+    Insertion(#[from] ScoreInsertionError),
+}
+
+#[derive(Debug, Error)]
+pub enum ScoreCorrectionError {
+    #[error("the user already has a score for that board")]
+    Conflict,
+
+    #[error("failed to begin transaction: {0}")]
+    BeginTxn(#[source] SqlxError),
+
+    #[error("failed to commit transaction: {0}")]
+    CommitTxn(#[source] SqlxError),
+
+    #[error("failed to extract data from row: {0}")]
+    FromRow(#[source] SqlxError),
+
+    #[error("unexpected SQLx error: {0}")]
+    Unexpected(SqlxError),
+
+    #[error(transparent)]
+    GuildUserInsertion(#[from] GuildUserInsertionError),
+}
+
+/// The message that a score was submitted in, so that its reactions can be updated.
+#[derive(Clone, Copy, Debug)]
+pub struct ScoreMessage {
+    pub message_id: Option<MessageId>,
+    pub channel_id: Option<ChannelId>,
+}
+
+#[derive(Clone, Debug, FromRow)]
+struct ScoreMessageRow {
+    message_id: Option<i64>,
+    channel_id: Option<i64>,
+}
+
+impl From<ScoreMessageRow> for ScoreMessage {
+    fn from(row: ScoreMessageRow) -> Self {
+        Self {
+            message_id: row.message_id.map(|id| MessageId::new(id as u64)),
+            channel_id: row.channel_id.map(|id| ChannelId::new(id as u64)),
+        }
+    }
+}
+
 pub trait InsertedScore {
     fn is_best_so_far(&self) -> bool;
     fn is_on_time(&self) -> bool;
+
+    /// The board the score is for, as given by [`ScoreTable::board`].
+    fn board(&self) -> usize;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flagle::Flagle, wordle::Wordle, Game};
+    use crate::settings::GuildSettings;
+
+    #[test]
+    fn ties_are_only_broken_by_time_when_turned_on() {
+        let tiebreak = GuildSettings {
+            flagle_tiebreak_by_time: true,
+            ..GuildSettings::default()
+        };
+
+        assert_eq!(
+            Flagle::SCORE_TABLE.order_by(&GuildSettings::default()),
+            "s.score DESC",
+        );
+        assert_eq!(
+            Flagle::SCORE_TABLE.order_by(&tiebreak),
+            "s.score DESC, s.submitted_at ASC NULLS LAST",
+        );
+        assert_eq!(Wordle::SCORE_TABLE.order_by(&tiebreak), "s.guesses ASC");
+    }
 }
@@ -0,0 +1,605 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use indoc::indoc;
+use serenity::{
+    all::UserId,
+    model::prelude::{GuildId, User},
+};
+use sqlx::{postgres::PgRow, Error as SqlxError, FromRow, PgPool, Row as _};
+use thiserror::Error;
+use tracing::{debug, error, info};
+
+use super::{
+    CalculateAllTimeError, CalculateDailyError, ScoreInsertionError, ScoreTable, Submission,
+};
+use crate::{
+    game::leaderboards::{AllTime, Daily},
+    persist::{insert_guild_user, GuildUserRow, InsertionTarget, UserRow},
+};
+
+pub mod leaderboards;
+pub mod utils;
+
+/// The number of mistakes after which a puzzle is failed.
+pub const MAX_MISTAKES: usize = 4;
+
+pub struct Connections;
+
+impl super::Game for Connections {
+    type Score = Score;
+    type DailyLeaderboard = Daily;
+    type AllTimeLeaderboard = AllTime;
+
+    fn slug() -> &'static str {
+        "connections"
+    }
+
+    fn display_name() -> &'static str {
+        "Connections"
+    }
+
+    const SCORE_TABLE: ScoreTable = ScoreTable {
+        name: "connections_scores",
+        board: "s.board",
+        on_time: "s.board = s.day_added",
+        score: "s.mistakes",
+        ranked: "s.solved",
+        higher_is_better: false,
+        time_tiebreak: false,
+    };
+
+    fn board_now() -> usize {
+        utils::board_now()
+    }
+
+    fn today() -> NaiveDate {
+        utils::today()
+    }
+
+    fn board_on_date(date: NaiveDate) -> Option<usize> {
+        utils::board_on_date(date)
+    }
+
+    async fn daily_leaderboard(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        board: usize,
+    ) -> Result<Daily, CalculateDailyError> {
+        Daily::calculate::<Self>(db_pool, guild_id, board).await
+    }
+
+    async fn all_time_leaderboard(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        include_today: bool,
+        include_late: bool,
+    ) -> Result<AllTime, CalculateAllTimeError> {
+        AllTime::calculate::<Self>(db_pool, guild_id, include_today, include_late).await
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Score {
+    pub mistakes: usize,
+    pub solved: bool,
+    pub purple_first: bool,
+    pub board: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Colour {
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl FromStr for Score {
+    type Err = ParseScoreError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let mut lines = raw.trim().lines();
+
+        if lines.next().ok_or(ParseScoreError::Empty)?.trim() != "Connections" {
+            return Err(ParseScoreError::Missing(Section::Title));
+        }
+
+        let board = lines
+            .next()
+            .ok_or(ParseScoreError::Truncated)?
+            .trim()
+            .strip_prefix("Puzzle #")
+            .ok_or(ParseScoreError::Missing(Section::PuzzleNumber))?
+            .replace(',', "")
+            .parse::<usize>()
+            .map_err(|_| ParseScoreError::NotANumber(Number::Puzzle))?;
+
+        let rows = lines
+            .map(str::trim)
+            .skip_while(|line| line.is_empty())
+            .take_while(|line| !line.is_empty())
+            .map(|line| {
+                let row = line
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(|c| match c {
+                        '🟨' => Some(Colour::Yellow),
+                        '🟩' => Some(Colour::Green),
+                        '🟦' => Some(Colour::Blue),
+                        '🟪' => Some(Colour::Purple),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(ParseScoreError::InvalidFormat(Section::Grid))?;
+
+                if row.len() != 4 {
+                    return Err(ParseScoreError::InvalidFormat(Section::Grid));
+                }
+
+                // A row is a correct guess if every square in it is the same colour
+                let solved = row.iter().all(|&colour| colour == row[0]).then_some(row[0]);
+                Ok(solved)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if rows.is_empty() {
+            return Err(ParseScoreError::Missing(Section::Grid));
+        }
+
+        let found = rows.iter().flatten().copied().collect::<Vec<_>>();
+        let mistakes = rows.len() - found.len();
+        let solved = found.len() == 4;
+        let last_row_solved = rows.last().is_some_and(Option::is_some);
+
+        // Each group can only be found once, and the puzzle ends either when the fourth group is
+        // found or when the last allowed mistake is made. Anything else means the grid has been
+        // tampered with or cut short.
+        let duplicate_group = found
+            .iter()
+            .enumerate()
+            .any(|(i, colour)| found[..i].contains(colour));
+        let consistent = !duplicate_group
+            && if solved {
+                mistakes < MAX_MISTAKES && last_row_solved
+            } else {
+                mistakes == MAX_MISTAKES && !last_row_solved
+            };
+
+        if !consistent {
+            return Err(ParseScoreError::Inconsistent);
+        }
+
+        Ok(Score {
+            mistakes,
+            solved,
+            // Mistakes made along the way don't count against finding purple first
+            purple_first: found.first() == Some(&Colour::Purple),
+            board,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum ParseScoreError {
+    #[error("string is empty")]
+    Empty,
+
+    #[error("string ends prematurely")]
+    Truncated,
+
+    #[error("string does not contain a {0}")]
+    Missing(Section),
+
+    #[error("{0} was not formatted as expected")]
+    InvalidFormat(Section),
+
+    #[error("{0} is not a number")]
+    NotANumber(Number),
+
+    #[error("grid is not a possible Connections result")]
+    Inconsistent,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Section {
+    Title,
+    PuzzleNumber,
+    Grid,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Section::Title => write!(f, "title line"),
+            Section::PuzzleNumber => write!(f, "puzzle number line"),
+            Section::Grid => write!(f, "grid section"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Number {
+    Puzzle,
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Puzzle => write!(f, "puzzle number"),
+        }
+    }
+}
+
+impl super::Score for Score {
+    type Game = Connections;
+
+    fn is_recorded_in(&self, row: &PgRow) -> Result<bool, SqlxError> {
+        Ok(ScoreRow::from_row(row)?.records(self))
+    }
+
+    async fn insert(
+        self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        user: &User,
+        submission: Submission,
+    ) -> Result<impl super::InsertedScore, ScoreInsertionError> {
+        let mut txn = db_pool
+            .begin()
+            .await
+            .map_err(ScoreInsertionError::BeginTxn)?;
+
+        insert_guild_user(&mut txn, guild_id, user).await?;
+
+        let score_row = ScoreRow::from_score_at_opt(self, guild_id, user.id, submission)
+            .ok_or(ScoreInsertionError::BeforeFirstBoard)?;
+
+        let get_best_score = sqlx::query(indoc! {"
+            SELECT mistakes FROM connections_scores
+            WHERE
+                guild_id = $1
+                AND user_id != $2
+                AND board = $3
+                AND board = day_added
+                AND solved
+            ORDER BY mistakes ASC
+            LIMIT 1;
+        "});
+        let best_so_far = match get_best_score
+            .bind(guild_id.get() as i64)
+            .bind(user.id.get() as i64)
+            .bind(score_row.board)
+            .fetch_one(txn.as_mut())
+            .await
+            .and_then(|row| row.try_get::<i32, _>(0))
+        {
+            Ok(best_mistakes) => {
+                info!(
+                    %best_mistakes,
+                    board = %score_row.board,
+                    "got best existing score for this board"
+                );
+
+                score_row.solved && score_row.mistakes <= best_mistakes
+            }
+            Err(SqlxError::RowNotFound) => {
+                info!(
+                    board = %score_row.board,
+                    "there are no on-time scores for this board"
+                );
+                score_row.solved
+            }
+            Err(error) => {
+                error!(
+                    %error,
+                    board = %score_row.board,
+                    "failed to get current best score for this board"
+                );
+                true
+            }
+        };
+
+        let insert_score = sqlx::query(indoc! {"
+            INSERT INTO connections_scores (
+                guild_id,
+                user_id,
+                mistakes,
+                solved,
+                purple_first,
+                board,
+                day_added,
+                message_id,
+                channel_id,
+                submitted_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10);
+        "});
+        match insert_score
+            .bind(score_row.guild_id)
+            .bind(score_row.user_id)
+            .bind(score_row.mistakes)
+            .bind(score_row.solved)
+            .bind(score_row.purple_first)
+            .bind(score_row.board)
+            .bind(score_row.day_added)
+            .bind(score_row.message_id)
+            .bind(score_row.channel_id)
+            .bind(score_row.submitted_at)
+            .execute(txn.as_mut())
+            .await
+        {
+            Ok(_) => info!("inserted new score"),
+            Err(SqlxError::Database(db_err)) if db_err.is_unique_violation() => {
+                info!("score was a duplicate");
+                return Err(ScoreInsertionError::Duplicate);
+            }
+            Err(error) => {
+                error!(%error, "failed to insert score");
+                return Err(ScoreInsertionError::UnexpectedSqlx {
+                    target: InsertionTarget::Score,
+                    error,
+                });
+            }
+        }
+
+        txn.commit().await.map_err(ScoreInsertionError::CommitTxn)?;
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, UserRow>("SELECT user_id FROM users")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(users) => {
+                for user in users {
+                    debug!(?user, "user");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get users");
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, GuildUserRow>("SELECT guild_id, user_id FROM guild_users")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(guild_users) => {
+                for guild_user in guild_users {
+                    debug!(?guild_user, "guild user");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get guild users");
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, ScoreRow>("SELECT * FROM connections_scores")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(scores) => {
+                for score in scores {
+                    debug!(?score, "score");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get scores");
+            }
+        }
+
+        Ok(InsertedScore {
+            best_so_far,
+            on_time: score_row.on_time(),
+            board: score_row.board as usize,
+        })
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct ScoreRow {
+    pub guild_id: i64,
+    pub user_id: i64,
+    pub mistakes: i32,
+    pub solved: bool,
+    pub purple_first: bool,
+    pub board: i32,
+    pub day_added: i32,
+    pub message_id: Option<i64>,
+    pub channel_id: Option<i64>,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+impl ScoreRow {
+    /// Whether this row records `score`, whenever and wherever it was submitted.
+    pub fn records(&self, score: &Score) -> bool {
+        (self.mistakes, self.solved, self.purple_first, self.board)
+            == (
+                score.mistakes as i32,
+                score.solved,
+                score.purple_first,
+                score.board as i32,
+            )
+    }
+
+    pub fn from_score_at_opt(
+        score: Score,
+        guild_id: GuildId,
+        user_id: UserId,
+        submission: Submission,
+    ) -> Option<Self> {
+        let Score {
+            mistakes,
+            solved,
+            purple_first,
+            board,
+        } = score;
+
+        Some(ScoreRow {
+            guild_id: guild_id.get() as i64,
+            user_id: user_id.get() as i64,
+            mistakes: mistakes as i32,
+            solved,
+            purple_first,
+            board: board as i32,
+            day_added: utils::board_on_date(utils::date_from_utc(submission.submitted_at))? as i32,
+            message_id: submission
+                .message_id
+                .map(|message_id| message_id.get() as i64),
+            channel_id: Some(submission.channel_id.get() as i64),
+            submitted_at: Some(submission.submitted_at),
+        })
+    }
+
+    pub fn on_time(&self) -> bool {
+        self.day_added == self.board
+    }
+}
+
+pub struct InsertedScore {
+    pub best_so_far: bool,
+    pub on_time: bool,
+    pub board: usize,
+}
+
+impl crate::game::InsertedScore for InsertedScore {
+    fn is_best_so_far(&self) -> bool {
+        self.best_so_far
+    }
+
+    fn is_on_time(&self) -> bool {
+        self.on_time
+    }
+
+    fn board(&self) -> usize {
+        self.board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::{ParseScoreError, Score};
+
+    #[test]
+    fn parse_perfect() {
+        let raw = indoc! {"
+            Connections
+            Puzzle #487
+            🟪🟪🟪🟪
+            🟦🟦🟦🟦
+            🟩🟩🟩🟩
+            🟨🟨🟨🟨
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 487);
+        assert_eq!(score.mistakes, 0);
+        assert!(score.solved);
+        assert!(score.purple_first);
+    }
+
+    #[test]
+    fn parse_with_mistakes() {
+        let raw = indoc! {"
+            Connections
+            Puzzle #512
+            🟨🟨🟨🟨
+            🟩🟦🟩🟩
+            🟩🟩🟩🟩
+            🟦🟪🟦🟦
+            🟦🟦🟦🟦
+            🟪🟪🟪🟪
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 512);
+        assert_eq!(score.mistakes, 2);
+        assert!(score.solved);
+        assert!(!score.purple_first);
+    }
+
+    #[test]
+    fn parse_purple_first_after_mistake() {
+        let raw = indoc! {"
+            Connections
+            Puzzle #530
+            🟪🟦🟪🟪
+            🟪🟪🟪🟪
+            🟦🟦🟦🟦
+            🟩🟩🟩🟩
+            🟨🟨🟨🟨
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.mistakes, 1);
+        assert!(score.solved);
+        assert!(score.purple_first);
+    }
+
+    #[test]
+    fn parse_failed() {
+        let raw = indoc! {"
+            Connections
+            Puzzle #1,004
+            🟨🟨🟨🟨
+            🟩🟦🟩🟩
+            🟩🟦🟩🟩
+            🟦🟪🟦🟦
+            🟩🟪🟦🟦
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 1004);
+        assert_eq!(score.mistakes, 4);
+        assert!(!score.solved);
+    }
+
+    #[test]
+    fn reject_unfinished() {
+        let raw = indoc! {"
+            Connections
+            Puzzle #512
+            🟨🟨🟨🟨
+            🟩🟦🟩🟩
+            🟩🟩🟩🟩
+        "};
+
+        assert!(matches!(
+            raw.parse::<Score>(),
+            Err(ParseScoreError::Inconsistent)
+        ));
+    }
+
+    #[test]
+    fn reject_repeated_group() {
+        let raw = indoc! {"
+            Connections
+            Puzzle #512
+            🟨🟨🟨🟨
+            🟨🟨🟨🟨
+            🟩🟩🟩🟩
+            🟦🟦🟦🟦
+        "};
+
+        assert!(matches!(
+            raw.parse::<Score>(),
+            Err(ParseScoreError::Inconsistent)
+        ));
+    }
+}
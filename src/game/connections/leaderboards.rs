@@ -0,0 +1,26 @@
+use sqlx::FromRow;
+
+use crate::game::{connections::Connections, leaderboards::Tabled};
+
+#[derive(Clone, Debug, FromRow)]
+pub struct Entry {
+    mistakes: i32,
+    purple_first: bool,
+}
+
+impl Tabled for Connections {
+    type Entry = Entry;
+
+    fn describe(entry: &Entry) -> String {
+        format!(
+            "({} {}){}",
+            entry.mistakes,
+            if entry.mistakes == 1 {
+                "mistake"
+            } else {
+                "mistakes"
+            },
+            if entry.purple_first { " 🟪" } else { "" },
+        )
+    }
+}
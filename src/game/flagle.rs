@@ -1,19 +1,22 @@
 use std::{fmt, str::FromStr};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use indoc::indoc;
 use serenity::{
-    all::{CreateEmbed, UserId},
+    all::UserId,
     model::prelude::{GuildId, User},
 };
-use sqlx::{Error as SqlxError, FromRow, Row as _};
+use sqlx::{postgres::PgRow, Error as SqlxError, FromRow, Row as _};
 use thiserror::Error;
 use tracing::{debug, error, info};
 
-use super::{CalculateAllTimeError, CalculateDailyError, ScoreInsertionError};
+use super::{
+    CalculateAllTimeError, CalculateDailyError, ScoreInsertionError, ScoreTable, Submission,
+};
 use crate::{
-    game::flagle::leaderboards::{AllTime, Daily},
+    game::leaderboards::{AllTime, Daily},
     persist::{insert_guild_user, GuildUserRow, InsertionTarget, UserRow},
+    settings::GuildSettings,
 };
 
 pub mod leaderboards;
@@ -23,16 +26,45 @@ pub struct Flagle;
 
 impl super::Game for Flagle {
     type Score = Score;
+    type DailyLeaderboard = Daily;
+    type AllTimeLeaderboard = AllTime;
+
+    fn slug() -> &'static str {
+        "flagle"
+    }
 
-    fn description() -> &'static str {
+    fn display_name() -> &'static str {
         "Flagle"
     }
 
+    const SCORE_TABLE: ScoreTable = ScoreTable {
+        name: "flagle_scores",
+        board: "s.board",
+        on_time: "s.board = s.day_added",
+        score: "s.score",
+        ranked: "s.score != 0",
+        higher_is_better: true,
+        time_tiebreak: true,
+    };
+
+    fn board_now() -> usize {
+        utils::board_now()
+    }
+
+    fn today() -> NaiveDate {
+        utils::today()
+    }
+
+    fn board_on_date(date: NaiveDate) -> Option<usize> {
+        utils::board_on_date(date)
+    }
+
     async fn daily_leaderboard(
         db_pool: &sqlx::PgPool,
         guild_id: GuildId,
-    ) -> Result<impl Into<CreateEmbed> + std::fmt::Debug, CalculateDailyError> {
-        Daily::calculate_for(db_pool, guild_id, utils::board_now()).await
+        board: usize,
+    ) -> Result<Daily, CalculateDailyError> {
+        Daily::calculate::<Self>(db_pool, guild_id, board).await
     }
 
     async fn all_time_leaderboard(
@@ -40,15 +72,8 @@ impl super::Game for Flagle {
         guild_id: GuildId,
         include_today: bool,
         include_late: bool,
-    ) -> Result<impl Into<CreateEmbed> + std::fmt::Debug, CalculateAllTimeError> {
-        AllTime::calculate(
-            db_pool,
-            guild_id,
-            utils::board_now(),
-            include_today,
-            include_late,
-        )
-        .await
+    ) -> Result<AllTime, CalculateAllTimeError> {
+        AllTime::calculate::<Self>(db_pool, guild_id, include_today, include_late).await
     }
 }
 
@@ -183,12 +208,25 @@ impl fmt::Display for Number {
 impl super::Score for Score {
     type Game = Flagle;
 
+    fn is_recorded_in(&self, row: &PgRow) -> Result<bool, SqlxError> {
+        Ok(ScoreRow::from_row(row)?.records(self))
+    }
+
     async fn insert(
         self,
         db_pool: &sqlx::PgPool,
         guild_id: GuildId,
         user: &User,
+        submission: Submission,
     ) -> Result<impl super::InsertedScore, ScoreInsertionError> {
+        let tiebreak_by_time = GuildSettings::get(db_pool, guild_id)
+            .await
+            .map(|settings| <Flagle as super::Game>::SCORE_TABLE.breaks_ties_by_time(&settings))
+            .unwrap_or_else(|error| {
+                error!(%error, "failed to fetch guild settings, not breaking ties");
+                false
+            });
+
         let mut txn = db_pool
             .begin()
             .await
@@ -196,16 +234,22 @@ impl super::Score for Score {
 
         insert_guild_user(&mut txn, guild_id, user).await?;
 
-        let score_row = ScoreRow::from_score_now(self, guild_id, user.id);
+        let score_row = ScoreRow::from_score_at_opt(self, guild_id, user.id, submission)
+            .ok_or(ScoreInsertionError::BeforeFirstBoard)?;
 
         let get_best_score = sqlx::query(indoc! {"
-            SELECT score FROM flagle_scores
+            SELECT
+                score,
+                submitted_at
+            FROM flagle_scores
             WHERE
                 guild_id = $1
                 AND user_id != $2
                 AND board = $3
                 AND board = day_added
-            ORDER BY score DESC
+            ORDER BY
+                score DESC,
+                submitted_at ASC NULLS LAST
             LIMIT 1;
         "});
         let best_so_far = match get_best_score
@@ -214,16 +258,30 @@ impl super::Score for Score {
             .bind(score_row.board)
             .fetch_one(txn.as_mut())
             .await
-            .and_then(|row| row.try_get::<i32, _>(0))
-        {
-            Ok(best_score) => {
+            .and_then(|row| {
+                Ok((
+                    row.try_get::<i32, _>(0)?,
+                    row.try_get::<Option<DateTime<Utc>>, _>(1)?,
+                ))
+            }) {
+            Ok((best_score, best_submitted_at)) => {
                 info!(
                     %best_score,
                     board = %score_row.board,
                     "got best existing score for this board"
                 );
 
+                // Without a tiebreak, an equal score shares the best. With one, scores can be
+                // recorded out of order when catching up on missed messages, so an equal score can
+                // still be the best if it was submitted first
                 score_row.score > best_score
+                    || (score_row.score == best_score
+                        && (!tiebreak_by_time
+                            || matches!(
+                                (score_row.submitted_at, best_submitted_at),
+                                (Some(submitted_at), Some(best_submitted_at))
+                                    if submitted_at < best_submitted_at
+                            )))
             }
             Err(SqlxError::RowNotFound) => {
                 info!(
@@ -248,9 +306,12 @@ impl super::Score for Score {
                 user_id,
                 score,
                 board,
-                day_added
+                day_added,
+                message_id,
+                channel_id,
+                submitted_at
             )
-            VALUES ($1, $2, $3, $4, $5);
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8);
         "});
         match insert_score
             .bind(score_row.guild_id)
@@ -258,6 +319,9 @@ impl super::Score for Score {
             .bind(score_row.score)
             .bind(score_row.board)
             .bind(score_row.day_added)
+            .bind(score_row.message_id)
+            .bind(score_row.channel_id)
+            .bind(score_row.submitted_at)
             .execute(txn.as_mut())
             .await
         {
@@ -325,6 +389,7 @@ impl super::Score for Score {
         Ok(InsertedScore {
             best_so_far,
             on_time: score_row.on_time(),
+            board: score_row.board as usize,
         })
     }
 }
@@ -336,19 +401,22 @@ pub struct ScoreRow {
     pub score: i32,
     pub board: i32,
     pub day_added: i32,
+    pub message_id: Option<i64>,
+    pub channel_id: Option<i64>,
+    pub submitted_at: Option<DateTime<Utc>>,
 }
 
 impl ScoreRow {
-    pub fn from_score_now(score: Score, guild_id: GuildId, user_id: UserId) -> Self {
-        Self::from_score_at_opt(score, guild_id, user_id, Utc::now())
-            .expect("now should always be after day 1")
+    /// Whether this row records `score`, whenever and wherever it was submitted.
+    pub fn records(&self, score: &Score) -> bool {
+        (self.score, self.board) == (score.score as i32, score.board as i32)
     }
 
     pub fn from_score_at_opt(
         score: Score,
         guild_id: GuildId,
         user_id: UserId,
-        submitted: DateTime<Utc>,
+        submission: Submission,
     ) -> Option<Self> {
         let Score { board, score } = score;
 
@@ -357,7 +425,12 @@ impl ScoreRow {
             user_id: user_id.get() as i64,
             score: score as i32,
             board: board as i32,
-            day_added: utils::board_on_date(utils::date_from_utc(submitted))? as i32,
+            day_added: utils::board_on_date(utils::date_from_utc(submission.submitted_at))? as i32,
+            message_id: submission
+                .message_id
+                .map(|message_id| message_id.get() as i64),
+            channel_id: Some(submission.channel_id.get() as i64),
+            submitted_at: Some(submission.submitted_at),
         })
     }
 
@@ -369,6 +442,7 @@ impl ScoreRow {
 pub struct InsertedScore {
     pub best_so_far: bool,
     pub on_time: bool,
+    pub board: usize,
 }
 
 impl crate::game::InsertedScore for InsertedScore {
@@ -379,4 +453,8 @@ impl crate::game::InsertedScore for InsertedScore {
     fn is_on_time(&self) -> bool {
         self.on_time
     }
+
+    fn board(&self) -> usize {
+        self.board
+    }
 }
@@ -1,35 +1,86 @@
 use std::{fmt, str::FromStr};
 
-use chrono::{Datelike, Month, NaiveDate, Utc};
+use chrono::{DateTime, Datelike, Days, Month, NaiveDate, Utc};
 use indoc::indoc;
 use serenity::{
-    all::{CreateEmbed, UserId},
+    all::UserId,
     model::prelude::{GuildId, User},
 };
-use sqlx::{Error as SqlxError, FromRow, PgPool, Row as _};
+use sqlx::{postgres::PgRow, Error as SqlxError, FromRow, PgPool, Row as _};
 use thiserror::Error;
 use tracing::{debug, error, info};
 
-use self::leaderboards::{AllTime, Daily};
-use super::{CalculateAllTimeError, CalculateDailyError, ScoreInsertionError};
-use crate::persist::{insert_guild_user, GuildUserRow, InsertionTarget, UserRow};
+use super::{
+    CalculateAllTimeError, CalculateDailyError, ScoreInsertionError, ScoreTable, Submission,
+};
+use crate::{
+    game::leaderboards::{AllTime, Daily},
+    persist::{insert_guild_user, GuildUserRow, InsertionTarget, UserRow},
+};
 
 pub mod leaderboards;
 
 pub struct FoodGuessr;
 
+/// Get a number for `date` that goes up by one each day. FoodGuessr identifies its boards by date
+/// rather than by number, so this stands in for a board number where one is needed, and matches
+/// the `board` expression in [`FoodGuessr`]'s score table.
+pub fn day_number(date: NaiveDate) -> usize {
+    (date - epoch()).num_days() as usize
+}
+
+/// Get the date that has the given [`day_number`].
+pub fn date_of_day_number(number: usize) -> NaiveDate {
+    epoch() + Days::new(number as u64)
+}
+
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).expect("1 January 1970 is a valid date")
+}
+
 impl super::Game for FoodGuessr {
     type Score = Score;
+    type DailyLeaderboard = Daily;
+    type AllTimeLeaderboard = AllTime;
+
+    fn slug() -> &'static str {
+        "foodguessr"
+    }
 
-    fn description() -> &'static str {
+    fn display_name() -> &'static str {
         "FoodGuessr"
     }
 
+    const SCORE_TABLE: ScoreTable = ScoreTable {
+        name: "foodguessr_scores",
+        board: "((make_date(s.year, 1, 1) + (s.ordinal - 1)) - DATE '1970-01-01')",
+        on_time: "s.year = s.year_added AND s.ordinal = s.ordinal_added",
+        score: "s.score",
+        ranked: "s.score != 0",
+        higher_is_better: true,
+        time_tiebreak: false,
+    };
+
+    const NUMBERED_BOARDS: bool = false;
+
+    fn board_now() -> usize {
+        day_number(Self::today())
+    }
+
+    fn today() -> NaiveDate {
+        Utc::now().naive_utc().date()
+    }
+
+    fn board_on_date(date: NaiveDate) -> Option<usize> {
+        (date >= epoch()).then(|| day_number(date))
+    }
+
     async fn daily_leaderboard(
         db_pool: &PgPool,
         guild_id: GuildId,
-    ) -> Result<impl Into<CreateEmbed> + fmt::Debug, CalculateDailyError> {
-        Daily::calculate_for(db_pool, guild_id, Utc::now().naive_utc().date()).await
+        board: usize,
+    ) -> Result<Daily, CalculateDailyError> {
+        Daily::calculate::<Self>(db_pool, guild_id, board).await
     }
 
     async fn all_time_leaderboard(
@@ -37,15 +88,8 @@ impl super::Game for FoodGuessr {
         guild_id: GuildId,
         include_today: bool,
         include_late: bool,
-    ) -> Result<impl Into<CreateEmbed> + fmt::Debug, CalculateAllTimeError> {
-        AllTime::calculate(
-            db_pool,
-            guild_id,
-            Utc::now().naive_utc().date(),
-            include_today,
-            include_late,
-        )
-        .await
+    ) -> Result<AllTime, CalculateAllTimeError> {
+        AllTime::calculate::<Self>(db_pool, guild_id, include_today, include_late).await
     }
 }
 
@@ -179,11 +223,16 @@ impl fmt::Display for Number {
 impl super::Score for Score {
     type Game = FoodGuessr;
 
+    fn is_recorded_in(&self, row: &PgRow) -> Result<bool, SqlxError> {
+        Ok(ScoreRow::from_row(row)?.records(self))
+    }
+
     async fn insert(
         self,
         db_pool: &PgPool,
         guild_id: GuildId,
         user: &User,
+        submission: Submission,
     ) -> Result<impl super::InsertedScore, ScoreInsertionError> {
         let mut txn = db_pool
             .begin()
@@ -192,7 +241,8 @@ impl super::Score for Score {
 
         insert_guild_user(&mut txn, guild_id, user).await?;
 
-        let score_row = ScoreRow::from_score_now(self, guild_id, user.id);
+        let score_row = ScoreRow::from_score_at_opt(self, guild_id, user.id, submission)
+            .ok_or(ScoreInsertionError::BeforeFirstBoard)?;
 
         let get_best_score = sqlx::query(indoc! {"
             SELECT score FROM foodguessr_scores
@@ -223,7 +273,7 @@ impl super::Score for Score {
                     "got best existing score for this board"
                 );
 
-                score_row.score > best_score
+                score_row.score >= best_score
             }
             Err(SqlxError::RowNotFound) => {
                 info!(
@@ -252,9 +302,12 @@ impl super::Score for Score {
                 year,
                 ordinal,
                 year_added,
-                ordinal_added
+                ordinal_added,
+                message_id,
+                channel_id,
+                submitted_at
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7);
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10);
         "});
         match insert_score
             .bind(score_row.guild_id)
@@ -264,6 +317,9 @@ impl super::Score for Score {
             .bind(score_row.ordinal)
             .bind(score_row.year_added)
             .bind(score_row.ordinal_added)
+            .bind(score_row.message_id)
+            .bind(score_row.channel_id)
+            .bind(score_row.submitted_at)
             .execute(txn.as_mut())
             .await
         {
@@ -331,6 +387,7 @@ impl super::Score for Score {
         Ok(InsertedScore {
             best_so_far,
             on_time: score_row.on_time(),
+            board: day_number(score_row.date()),
         })
     }
 }
@@ -344,21 +401,30 @@ pub struct ScoreRow {
     pub ordinal: i32,
     pub year_added: i32,
     pub ordinal_added: i32,
+    pub message_id: Option<i64>,
+    pub channel_id: Option<i64>,
+    pub submitted_at: Option<DateTime<Utc>>,
 }
 
 impl ScoreRow {
-    pub fn from_score_now(score: Score, guild_id: GuildId, user_id: UserId) -> Self {
-        Self::from_score_at_opt(score, guild_id, user_id, Utc::now().naive_utc().date())
-            .expect("now should always be after day 1")
+    /// Whether this row records `score`, whenever and wherever it was submitted.
+    pub fn records(&self, score: &Score) -> bool {
+        (self.score, self.year, self.ordinal)
+            == (
+                score.score as i32,
+                score.date.year(),
+                score.date.ordinal() as i32,
+            )
     }
 
     pub fn from_score_at_opt(
         score: Score,
         guild_id: GuildId,
         user_id: UserId,
-        submitted: NaiveDate,
+        submission: Submission,
     ) -> Option<Self> {
         let Score { date, score } = score;
+        let submitted = submission.submitted_at.naive_utc().date();
 
         Some(ScoreRow {
             guild_id: guild_id.get() as i64,
@@ -368,17 +434,28 @@ impl ScoreRow {
             ordinal: date.ordinal() as i32,
             year_added: submitted.year(),
             ordinal_added: submitted.ordinal() as i32,
+            message_id: submission
+                .message_id
+                .map(|message_id| message_id.get() as i64),
+            channel_id: Some(submission.channel_id.get() as i64),
+            submitted_at: Some(submission.submitted_at),
         })
     }
 
     pub fn on_time(&self) -> bool {
         self.year == self.year_added && self.ordinal == self.ordinal_added
     }
+
+    pub fn date(&self) -> NaiveDate {
+        NaiveDate::from_yo_opt(self.year, self.ordinal as u32)
+            .expect("rows are only created from valid dates")
+    }
 }
 
 pub struct InsertedScore {
     pub best_so_far: bool,
     pub on_time: bool,
+    pub board: usize,
 }
 
 impl crate::game::InsertedScore for InsertedScore {
@@ -389,4 +466,8 @@ impl crate::game::InsertedScore for InsertedScore {
     fn is_on_time(&self) -> bool {
         self.on_time
     }
+
+    fn board(&self) -> usize {
+        self.board
+    }
 }
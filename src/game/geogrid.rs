@@ -1,15 +1,13 @@
 use std::{fmt, str::FromStr};
 
-use serenity::{
-    all::CreateEmbed,
-    model::prelude::{GuildId, User},
-};
-use sqlx::PgPool;
+use chrono::NaiveDate;
+use serenity::model::prelude::{GuildId, User};
+use sqlx::{postgres::PgRow, Error as SqlxError, FromRow as _, PgPool};
 use thiserror::Error;
 
 use crate::game::{
-    geogrid::leaderboards::{AllTime, Daily},
-    CalculateAllTimeError, CalculateDailyError, ScoreInsertionError,
+    leaderboards::{AllTime, Daily},
+    CalculateAllTimeError, CalculateDailyError, ScoreInsertionError, ScoreTable, Submission,
 };
 
 pub mod leaderboards;
@@ -20,16 +18,45 @@ pub struct GeoGrid;
 
 impl super::Game for GeoGrid {
     type Score = Score;
+    type DailyLeaderboard = Daily;
+    type AllTimeLeaderboard = AllTime;
+
+    fn slug() -> &'static str {
+        "geogrid"
+    }
 
-    fn description() -> &'static str {
+    fn display_name() -> &'static str {
         "GeoGrid"
     }
 
+    const SCORE_TABLE: ScoreTable = ScoreTable {
+        name: "geogrid_scores",
+        board: "s.board",
+        on_time: "s.board = s.day_added",
+        score: "s.score",
+        ranked: "TRUE",
+        higher_is_better: false,
+        time_tiebreak: false,
+    };
+
+    fn board_now() -> usize {
+        utils::board_now()
+    }
+
+    fn today() -> NaiveDate {
+        utils::today()
+    }
+
+    fn board_on_date(date: NaiveDate) -> Option<usize> {
+        utils::board_on_date(date)
+    }
+
     async fn daily_leaderboard(
         db_pool: &PgPool,
         guild_id: GuildId,
-    ) -> Result<impl Into<CreateEmbed> + fmt::Debug, CalculateDailyError> {
-        Daily::calculate_for(db_pool, guild_id, utils::board_now()).await
+        board: usize,
+    ) -> Result<Daily, CalculateDailyError> {
+        Daily::calculate::<Self>(db_pool, guild_id, board).await
     }
 
     async fn all_time_leaderboard(
@@ -37,28 +64,26 @@ impl super::Game for GeoGrid {
         guild_id: GuildId,
         include_today: bool,
         include_late: bool,
-    ) -> Result<impl Into<CreateEmbed> + fmt::Debug, CalculateAllTimeError> {
-        AllTime::calculate(
-            db_pool,
-            guild_id,
-            utils::board_now(),
-            include_today,
-            include_late,
-        )
-        .await
+    ) -> Result<AllTime, CalculateAllTimeError> {
+        AllTime::calculate::<Self>(db_pool, guild_id, include_today, include_late).await
     }
 }
 
 impl super::Score for Score {
     type Game = GeoGrid;
 
+    fn is_recorded_in(&self, row: &PgRow) -> Result<bool, SqlxError> {
+        Ok(persist::ScoreRow::from_row(row)?.records(self))
+    }
+
     async fn insert(
         self,
         db_pool: &PgPool,
         guild_id: GuildId,
         user: &User,
+        submission: Submission,
     ) -> Result<impl super::InsertedScore, ScoreInsertionError> {
-        persist::insert_score(db_pool, self, guild_id, user).await
+        persist::insert_score(db_pool, self, guild_id, user, submission).await
     }
 }
 
@@ -7,7 +7,7 @@ use tracing::{debug, error, info};
 use crate::{
     game::{
         geogrid::{utils, Score},
-        ScoreInsertionError,
+        ScoreInsertionError, Submission,
     },
     persist::{insert_guild_user, GuildUserRow, InsertionTarget, UserRow},
 };
@@ -22,19 +22,34 @@ pub struct ScoreRow {
     pub rank: i32,
     pub players: i32,
     pub day_added: i32,
+    pub message_id: Option<i64>,
+    pub channel_id: Option<i64>,
+    pub submitted_at: Option<DateTime<Utc>>,
 }
 
 impl ScoreRow {
-    pub fn from_score_now(score: Score, guild_id: GuildId, user_id: UserId) -> Self {
-        Self::from_score_at_opt(score, guild_id, user_id, Utc::now())
-            .expect("now should always be after day 1")
+    /// Whether this row records `score`, whenever and wherever it was submitted.
+    pub fn records(&self, score: &Score) -> bool {
+        (
+            self.correct,
+            self.board,
+            self.score,
+            self.rank,
+            self.players,
+        ) == (
+            score.correct as i32,
+            score.board as i32,
+            score.score,
+            score.rank as i32,
+            score.players as i32,
+        )
     }
 
     pub fn from_score_at_opt(
         score: Score,
         guild_id: GuildId,
         user_id: UserId,
-        submitted: DateTime<Utc>,
+        submission: Submission,
     ) -> Option<Self> {
         let Score {
             correct,
@@ -52,7 +67,12 @@ impl ScoreRow {
             score,
             rank: rank as i32,
             players: players as i32,
-            day_added: utils::board_on_date(utils::date_from_utc(submitted))? as i32,
+            day_added: utils::board_on_date(utils::date_from_utc(submission.submitted_at))? as i32,
+            message_id: submission
+                .message_id
+                .map(|message_id| message_id.get() as i64),
+            channel_id: Some(submission.channel_id.get() as i64),
+            submitted_at: Some(submission.submitted_at),
         })
     }
 
@@ -76,6 +96,7 @@ impl From<ScoreRow> for Score {
 pub struct InsertedScore {
     pub best_so_far: bool,
     pub on_time: bool,
+    pub board: usize,
 }
 
 impl crate::game::InsertedScore for InsertedScore {
@@ -86,6 +107,10 @@ impl crate::game::InsertedScore for InsertedScore {
     fn is_on_time(&self) -> bool {
         self.on_time
     }
+
+    fn board(&self) -> usize {
+        self.board
+    }
 }
 
 pub async fn insert_score(
@@ -93,6 +118,7 @@ pub async fn insert_score(
     score: Score,
     guild_id: GuildId,
     user: &User,
+    submission: Submission,
 ) -> Result<InsertedScore, ScoreInsertionError> {
     let mut txn = db_pool
         .begin()
@@ -101,7 +127,8 @@ pub async fn insert_score(
 
     insert_guild_user(&mut txn, guild_id, user).await?;
 
-    let score_row = ScoreRow::from_score_now(score, guild_id, user.id);
+    let score_row = ScoreRow::from_score_at_opt(score, guild_id, user.id, submission)
+        .ok_or(ScoreInsertionError::BeforeFirstBoard)?;
 
     let insert_score = sqlx::query(indoc! {"
         INSERT INTO geogrid_scores (
@@ -112,9 +139,12 @@ pub async fn insert_score(
             score,
             rank,
             players,
-            day_added
+            day_added,
+            message_id,
+            channel_id,
+            submitted_at
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8);
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11);
     "});
     match insert_score
         .bind(score_row.guild_id)
@@ -125,6 +155,9 @@ pub async fn insert_score(
         .bind(score_row.rank)
         .bind(score_row.players)
         .bind(score_row.day_added)
+        .bind(score_row.message_id)
+        .bind(score_row.channel_id)
+        .bind(score_row.submitted_at)
         .execute(txn.as_mut())
         .await
     {
@@ -143,7 +176,7 @@ pub async fn insert_score(
     }
 
     let get_best_score = sqlx::query(indoc! {"
-        SELECT user_id FROM geogrid_scores
+        SELECT score FROM geogrid_scores
         WHERE
             guild_id = $1
             AND board = $2
@@ -156,16 +189,18 @@ pub async fn insert_score(
         .bind(score_row.board)
         .fetch_one(txn.as_mut())
         .await
-        .and_then(|row| row.try_get::<i64, _>(0))
+        .and_then(|row| row.try_get::<f32, _>(0))
     {
-        Ok(best_user_id) => {
+        Ok(best_score) => {
             info!(
-                %best_user_id,
+                %best_score,
                 board = %score_row.board,
                 "got best score for this board"
             );
 
-            best_user_id == user.id.get() as i64
+            // The new score is already included, so it's the best (or tied for it) if nothing
+            // beats it
+            score_row.score <= best_score
         }
         Err(SqlxError::RowNotFound) => {
             info!(
@@ -234,5 +269,6 @@ pub async fn insert_score(
     Ok(InsertedScore {
         best_so_far,
         on_time: score_row.on_time(),
+        board: score_row.board as usize,
     })
 }
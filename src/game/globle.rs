@@ -0,0 +1,495 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use indoc::indoc;
+use serenity::{
+    all::UserId,
+    model::prelude::{GuildId, User},
+};
+use sqlx::{postgres::PgRow, Error as SqlxError, FromRow, PgPool, Row as _};
+use thiserror::Error;
+use tracing::{debug, error, info};
+
+use super::{
+    CalculateAllTimeError, CalculateDailyError, ScoreInsertionError, ScoreTable, Submission,
+};
+use crate::{
+    game::leaderboards::{AllTime, Daily},
+    persist::{insert_guild_user, GuildUserRow, InsertionTarget, UserRow},
+};
+
+pub mod leaderboards;
+pub mod utils;
+
+pub struct Globle;
+
+impl super::Game for Globle {
+    type Score = Score;
+    type DailyLeaderboard = Daily;
+    type AllTimeLeaderboard = AllTime;
+
+    fn slug() -> &'static str {
+        "globle"
+    }
+
+    fn display_name() -> &'static str {
+        "Globle"
+    }
+
+    const SCORE_TABLE: ScoreTable = ScoreTable {
+        name: "globle_scores",
+        board: "s.board",
+        on_time: "s.board = s.day_added",
+        score: "s.guesses",
+        ranked: "TRUE",
+        higher_is_better: false,
+        time_tiebreak: false,
+    };
+
+    fn board_now() -> usize {
+        utils::board_now()
+    }
+
+    fn today() -> NaiveDate {
+        utils::today()
+    }
+
+    fn board_on_date(date: NaiveDate) -> Option<usize> {
+        utils::board_on_date(date)
+    }
+
+    async fn daily_leaderboard(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        board: usize,
+    ) -> Result<Daily, CalculateDailyError> {
+        Daily::calculate::<Self>(db_pool, guild_id, board).await
+    }
+
+    async fn all_time_leaderboard(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        include_today: bool,
+        include_late: bool,
+    ) -> Result<AllTime, CalculateAllTimeError> {
+        AllTime::calculate::<Self>(db_pool, guild_id, include_today, include_late).await
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Score {
+    pub guesses: usize,
+    pub board: usize,
+}
+
+impl FromStr for Score {
+    type Err = ParseScoreError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let mut lines = raw.trim().lines();
+
+        let date_str = lines
+            .next()
+            .ok_or(ParseScoreError::Empty)?
+            .trim()
+            .strip_prefix("🌎 ")
+            .and_then(|line| line.strip_suffix(" 🌍"))
+            .ok_or(ParseScoreError::Missing(Section::Date))?;
+
+        let date = NaiveDate::parse_from_str(date_str, "%b %d, %Y")
+            .map_err(|_| ParseScoreError::InvalidFormat(Section::Date))?;
+
+        // Globle doesn't number its boards, so derive a number from the date instead
+        let board =
+            utils::board_on_date(date).ok_or(ParseScoreError::InvalidFormat(Section::Date))?;
+
+        // The streak line comes before the grid, and long grids wrap over several lines, with the
+        // total number of guesses after an equals sign at the end of the last one.
+        let mut squares = 0;
+        let mut total = None;
+
+        for line in lines.map(str::trim) {
+            let (grid, count) = match line.split_once('=') {
+                Some((grid, count)) => (grid, Some(count)),
+                None => (line, None),
+            };
+
+            let is_grid_line = !grid.trim().is_empty()
+                && grid
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .all(|c| matches!(c, '🟥' | '🟧' | '🟨' | '🟩' | '⬜'));
+
+            if !is_grid_line {
+                if squares > 0 {
+                    return Err(ParseScoreError::InvalidFormat(Section::Grid));
+                }
+                continue;
+            }
+
+            squares += grid.chars().filter(|c| !c.is_whitespace()).count();
+
+            if let Some(count) = count {
+                total = Some(
+                    count
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|_| ParseScoreError::NotANumber(Number::Guesses))?,
+                );
+                break;
+            }
+        }
+
+        if squares == 0 {
+            return Err(ParseScoreError::Missing(Section::Grid));
+        }
+
+        let guesses = total.ok_or(ParseScoreError::Missing(Section::Guesses))?;
+
+        // Every guess gets exactly one square, so if these don't match the score has been
+        // tampered with
+        if squares != guesses {
+            return Err(ParseScoreError::Inconsistent);
+        }
+
+        Ok(Score { guesses, board })
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum ParseScoreError {
+    #[error("string is empty")]
+    Empty,
+
+    #[error("string does not contain a {0}")]
+    Missing(Section),
+
+    #[error("{0} was not formatted as expected")]
+    InvalidFormat(Section),
+
+    #[error("{0} is not a number")]
+    NotANumber(Number),
+
+    #[error("guess count and grid don't match")]
+    Inconsistent,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Section {
+    Date,
+    Grid,
+    Guesses,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Section::Date => write!(f, "date line"),
+            Section::Grid => write!(f, "grid section"),
+            Section::Guesses => write!(f, "guess count"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Number {
+    Guesses,
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Guesses => write!(f, "guess count"),
+        }
+    }
+}
+
+impl super::Score for Score {
+    type Game = Globle;
+
+    fn is_recorded_in(&self, row: &PgRow) -> Result<bool, SqlxError> {
+        Ok(ScoreRow::from_row(row)?.records(self))
+    }
+
+    async fn insert(
+        self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        user: &User,
+        submission: Submission,
+    ) -> Result<impl super::InsertedScore, ScoreInsertionError> {
+        let mut txn = db_pool
+            .begin()
+            .await
+            .map_err(ScoreInsertionError::BeginTxn)?;
+
+        insert_guild_user(&mut txn, guild_id, user).await?;
+
+        let score_row = ScoreRow::from_score_at_opt(self, guild_id, user.id, submission)
+            .ok_or(ScoreInsertionError::BeforeFirstBoard)?;
+
+        let get_best_score = sqlx::query(indoc! {"
+            SELECT guesses FROM globle_scores
+            WHERE
+                guild_id = $1
+                AND user_id != $2
+                AND board = $3
+                AND board = day_added
+            ORDER BY guesses ASC
+            LIMIT 1;
+        "});
+        let best_so_far = match get_best_score
+            .bind(guild_id.get() as i64)
+            .bind(user.id.get() as i64)
+            .bind(score_row.board)
+            .fetch_one(txn.as_mut())
+            .await
+            .and_then(|row| row.try_get::<i32, _>(0))
+        {
+            Ok(best_guesses) => {
+                info!(
+                    %best_guesses,
+                    board = %score_row.board,
+                    "got best existing score for this board"
+                );
+
+                score_row.guesses <= best_guesses
+            }
+            Err(SqlxError::RowNotFound) => {
+                info!(
+                    board = %score_row.board,
+                    "there are no on-time scores for this board"
+                );
+                true
+            }
+            Err(error) => {
+                error!(
+                    %error,
+                    board = %score_row.board,
+                    "failed to get current best score for this board"
+                );
+                true
+            }
+        };
+
+        let insert_score = sqlx::query(indoc! {"
+            INSERT INTO globle_scores (
+                guild_id,
+                user_id,
+                guesses,
+                board,
+                day_added,
+                message_id,
+                channel_id,
+                submitted_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8);
+        "});
+        match insert_score
+            .bind(score_row.guild_id)
+            .bind(score_row.user_id)
+            .bind(score_row.guesses)
+            .bind(score_row.board)
+            .bind(score_row.day_added)
+            .bind(score_row.message_id)
+            .bind(score_row.channel_id)
+            .bind(score_row.submitted_at)
+            .execute(txn.as_mut())
+            .await
+        {
+            Ok(_) => info!("inserted new score"),
+            Err(SqlxError::Database(db_err)) if db_err.is_unique_violation() => {
+                info!("score was a duplicate");
+                return Err(ScoreInsertionError::Duplicate);
+            }
+            Err(error) => {
+                error!(%error, "failed to insert score");
+                return Err(ScoreInsertionError::UnexpectedSqlx {
+                    target: InsertionTarget::Score,
+                    error,
+                });
+            }
+        }
+
+        txn.commit().await.map_err(ScoreInsertionError::CommitTxn)?;
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, UserRow>("SELECT user_id FROM users")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(users) => {
+                for user in users {
+                    debug!(?user, "user");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get users");
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, GuildUserRow>("SELECT guild_id, user_id FROM guild_users")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(guild_users) => {
+                for guild_user in guild_users {
+                    debug!(?guild_user, "guild user");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get guild users");
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, ScoreRow>("SELECT * FROM globle_scores")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(scores) => {
+                for score in scores {
+                    debug!(?score, "score");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get scores");
+            }
+        }
+
+        Ok(InsertedScore {
+            best_so_far,
+            on_time: score_row.on_time(),
+            board: score_row.board as usize,
+        })
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct ScoreRow {
+    pub guild_id: i64,
+    pub user_id: i64,
+    pub guesses: i32,
+    pub board: i32,
+    pub day_added: i32,
+    pub message_id: Option<i64>,
+    pub channel_id: Option<i64>,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+impl ScoreRow {
+    /// Whether this row records `score`, whenever and wherever it was submitted.
+    pub fn records(&self, score: &Score) -> bool {
+        (self.guesses, self.board) == (score.guesses as i32, score.board as i32)
+    }
+
+    pub fn from_score_at_opt(
+        score: Score,
+        guild_id: GuildId,
+        user_id: UserId,
+        submission: Submission,
+    ) -> Option<Self> {
+        let Score { guesses, board } = score;
+
+        Some(ScoreRow {
+            guild_id: guild_id.get() as i64,
+            user_id: user_id.get() as i64,
+            guesses: guesses as i32,
+            board: board as i32,
+            day_added: utils::board_on_date(utils::date_from_utc(submission.submitted_at))? as i32,
+            message_id: submission
+                .message_id
+                .map(|message_id| message_id.get() as i64),
+            channel_id: Some(submission.channel_id.get() as i64),
+            submitted_at: Some(submission.submitted_at),
+        })
+    }
+
+    pub fn on_time(&self) -> bool {
+        self.day_added == self.board
+    }
+}
+
+pub struct InsertedScore {
+    pub best_so_far: bool,
+    pub on_time: bool,
+    pub board: usize,
+}
+
+impl crate::game::InsertedScore for InsertedScore {
+    fn is_best_so_far(&self) -> bool {
+        self.best_so_far
+    }
+
+    fn is_on_time(&self) -> bool {
+        self.on_time
+    }
+
+    fn board(&self) -> usize {
+        self.board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::{utils, ParseScoreError, Score};
+
+    #[test]
+    fn parse_short() {
+        let raw = indoc! {"
+            🌎 Oct 17, 2024 🌍
+            🔥 3 | Avg. Guesses: 5.2
+            🟧🟨🟩 = 3
+
+            https://globle-game.com
+            #globle
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 10, 17).expect("valid date");
+        assert_eq!(
+            score.board,
+            utils::board_on_date(date).expect("after day 1")
+        );
+        assert_eq!(score.guesses, 3);
+    }
+
+    #[test]
+    fn parse_wrapped_grid() {
+        let raw = indoc! {"
+            🌎 Mar 14, 2023 🌍
+            🔥 1 | Avg. Guesses: 8.5
+            ⬜🟨🟧🟨🟧🟥🟧🟥
+            🟥🟩 = 10
+
+            https://globle-game.com
+            #globle
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.guesses, 10);
+    }
+
+    #[test]
+    fn reject_count_mismatch() {
+        let raw = indoc! {"
+            🌎 Oct 17, 2024 🌍
+            🔥 3 | Avg. Guesses: 5.2
+            🟧🟨🟩 = 2
+        "};
+
+        assert!(matches!(
+            raw.parse::<Score>(),
+            Err(ParseScoreError::Inconsistent)
+        ));
+    }
+}
@@ -0,0 +1,24 @@
+use sqlx::FromRow;
+
+use crate::game::{globle::Globle, leaderboards::Tabled};
+
+#[derive(Clone, Debug, FromRow)]
+pub struct Entry {
+    guesses: i32,
+}
+
+impl Tabled for Globle {
+    type Entry = Entry;
+
+    fn describe(entry: &Entry) -> String {
+        format!(
+            "({} {})",
+            entry.guesses,
+            if entry.guesses == 1 {
+                "guess"
+            } else {
+                "guesses"
+            },
+        )
+    }
+}
@@ -0,0 +1,1247 @@
+use std::{cmp::Ordering, collections::HashMap, fmt, fmt::Write as _, ops::AddAssign};
+
+use chrono::{Datelike, Days, NaiveDate};
+use indoc::formatdoc;
+use serenity::all::{CreateEmbed, CreateEmbedFooter, GuildId, Mention, User, UserId};
+use sqlx::{postgres::PgRow, FromRow, PgPool, Row as _};
+use tracing::{debug, error, info};
+
+use crate::{
+    game::{CalculateAllTimeError, CalculateDailyError, DynGame, Game},
+    settings::GuildSettings,
+};
+
+/// The places a user has finished in across a number of boards. Entries are compared field by
+/// field, which is only used to give listings a stable order; how entries are ranked against each
+/// other on a leaderboard is up to a [`RankingStrategy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MedalsEntry {
+    pub gold: usize,
+    pub silver: usize,
+    pub bronze: usize,
+
+    /// The number of boards that were placed on, with or without a medal.
+    pub boards: usize,
+
+    /// The sum of the places on every board, for working out the average.
+    pub total_places: usize,
+}
+
+impl MedalsEntry {
+    /// Count a finish in `place` on one more board.
+    pub fn record(&mut self, place: usize) {
+        match place {
+            1 => self.gold += 1,
+            2 => self.silver += 1,
+            3 => self.bronze += 1,
+            _ => {}
+        }
+
+        self.boards += 1;
+        self.total_places += place;
+    }
+
+    pub fn medals(&self) -> usize {
+        self.gold + self.silver + self.bronze
+    }
+
+    pub fn average_place(&self) -> Option<f64> {
+        (self.boards > 0).then(|| self.total_places as f64 / self.boards as f64)
+    }
+}
+
+impl AddAssign for MedalsEntry {
+    fn add_assign(&mut self, other: Self) {
+        self.gold += other.gold;
+        self.silver += other.silver;
+        self.bronze += other.bronze;
+        self.boards += other.boards;
+        self.total_places += other.total_places;
+    }
+}
+
+impl fmt::Display for MedalsEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "🥇{} 🥈{} 🥉{}", self.gold, self.silver, self.bronze)
+    }
+}
+
+/// Tally up the medals won by each user, from the place they finished in on each board.
+pub fn tally(
+    places: impl IntoIterator<Item = (UserId, i64)>,
+) -> Result<HashMap<UserId, MedalsEntry>, CalculateAllTimeError> {
+    let mut medals = HashMap::<UserId, MedalsEntry>::default();
+
+    for (user_id, place) in places {
+        let place = usize::try_from(place)
+            .ok()
+            .filter(|place| *place > 0)
+            .ok_or(CalculateAllTimeError::PlaceOutOfBounds(place))?;
+
+        medals.entry(user_id).or_default().record(place);
+    }
+
+    Ok(medals)
+}
+
+/// How a guild's all-time and windowed leaderboards rank players by their medals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RankingStrategy {
+    /// Most points, with a number of points awarded for each medal.
+    Points {
+        gold: usize,
+        silver: usize,
+        bronze: usize,
+    },
+
+    /// Most golds, then most silvers, then most bronzes, like an Olympic medal table.
+    Olympic,
+
+    /// Most medals of any colour.
+    TotalMedals,
+
+    /// Lowest average place across every board played, including those without a medal.
+    AveragePlace,
+}
+
+impl Default for RankingStrategy {
+    fn default() -> Self {
+        Self::Points {
+            gold: 4,
+            silver: 2,
+            bronze: 1,
+        }
+    }
+}
+
+impl RankingStrategy {
+    /// The names used to store each strategy and to choose one in `/config`.
+    pub const SLUGS: &'static [(&'static str, &'static str)] = &[
+        ("points", "Medal points"),
+        ("olympic", "Olympic order"),
+        ("total", "Total medals"),
+        ("average", "Average place"),
+    ];
+
+    /// Look up the ranking strategy `slug`, using `points` as the weights if it's
+    /// [`RankingStrategy::Points`].
+    pub fn from_slug(slug: &str, points: (usize, usize, usize)) -> Option<Self> {
+        let (gold, silver, bronze) = points;
+        match slug {
+            "points" => Some(Self::Points {
+                gold,
+                silver,
+                bronze,
+            }),
+            "olympic" => Some(Self::Olympic),
+            "total" => Some(Self::TotalMedals),
+            "average" => Some(Self::AveragePlace),
+            _ => None,
+        }
+    }
+
+    pub fn slug(&self) -> &'static str {
+        match self {
+            Self::Points { .. } => "points",
+            Self::Olympic => "olympic",
+            Self::TotalMedals => "total",
+            Self::AveragePlace => "average",
+        }
+    }
+
+    /// The ranking strategy configured for `guild_id`, or the default if it can't be fetched.
+    pub async fn for_guild(db_pool: &PgPool, guild_id: GuildId) -> Self {
+        GuildSettings::get(db_pool, guild_id)
+            .await
+            .map(|settings| settings.ranking)
+            .unwrap_or_else(|error| {
+                error!(%error, "failed to fetch guild settings, using default ranking");
+                Self::default()
+            })
+    }
+
+    /// Compare two entries so that the better one is ordered first. Entries that compare equal
+    /// share a place.
+    pub fn compare(&self, a: &MedalsEntry, b: &MedalsEntry) -> Ordering {
+        match *self {
+            Self::Points { .. } => self.points(b).cmp(&self.points(a)),
+            Self::Olympic => (b.gold, b.silver, b.bronze).cmp(&(a.gold, a.silver, a.bronze)),
+            Self::TotalMedals => b.medals().cmp(&a.medals()),
+            // Compare the averages without dividing, so that equal averages are exactly equal
+            Self::AveragePlace => (a.total_places * b.boards).cmp(&(b.total_places * a.boards)),
+        }
+    }
+
+    /// Sort a listing from best to worst. Entries that share a place are ordered consistently, but
+    /// arbitrarily.
+    pub fn sort(&self, listing: &mut [(UserId, MedalsEntry)]) {
+        listing.sort_by(|(a_id, a), (b_id, b)| {
+            self.compare(a, b)
+                .then_with(|| b.cmp(a))
+                .then_with(|| a_id.cmp(b_id))
+        });
+    }
+
+    /// Work out the place of each entry in a listing sorted by [`RankingStrategy::sort`].
+    pub fn places(&self, listing: &[(UserId, MedalsEntry)]) -> Vec<usize> {
+        places_by(listing, |(_, a), (_, b)| self.compare(a, b).is_eq())
+    }
+
+    fn points(&self, entry: &MedalsEntry) -> usize {
+        match *self {
+            Self::Points {
+                gold,
+                silver,
+                bronze,
+            } => entry.gold * gold + entry.silver * silver + entry.bronze * bronze,
+            _ => 0,
+        }
+    }
+
+    /// Describe an entry, including whatever it's being ranked by.
+    pub fn describe(&self, entry: &MedalsEntry) -> String {
+        match self {
+            Self::Points { .. } => format!("{} (Medal points: {})", entry, self.points(entry)),
+            Self::Olympic => format!("{}", entry),
+            Self::TotalMedals => format!("{} (Medals: {})", entry, entry.medals()),
+            Self::AveragePlace => match entry.average_place() {
+                Some(average) => format!("{} (Average place: {:.2})", entry, average),
+                None => format!("{}", entry),
+            },
+        }
+    }
+}
+
+impl fmt::Display for RankingStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Points {
+                gold,
+                silver,
+                bronze,
+            } => write!(f, "Medal points ({}/{}/{})", gold, silver, bronze),
+            Self::Olympic => write!(f, "Olympic order"),
+            Self::TotalMedals => write!(f, "Total medals"),
+            Self::AveragePlace => write!(f, "Average place"),
+        }
+    }
+}
+
+/// A game whose daily and all-time leaderboards are worked out entirely from its
+/// [`Game::SCORE_TABLE`], so that all it has to say for itself is how each score is shown.
+pub trait Tabled: Game {
+    /// The columns of a score that are shown on a [`Daily`] leaderboard.
+    type Entry: for<'r> FromRow<'r, PgRow> + fmt::Debug + Send + Unpin;
+
+    /// How `entry` is shown after the player's name on a [`Daily`] leaderboard, e.g. "(3/6)".
+    fn describe(entry: &Self::Entry) -> String;
+}
+
+/// The on-time scores for one board of a [`Tabled`] game, best first.
+#[derive(Clone, Debug)]
+pub struct Daily {
+    game: &'static str,
+    board: usize,
+
+    /// The date of the board, for games whose boards don't have numbers.
+    date: Option<NaiveDate>,
+    today: bool,
+    tiebreak_by_time: bool,
+    pub entries: Vec<DailyEntry>,
+}
+
+impl Daily {
+    pub async fn calculate<G>(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        board: usize,
+    ) -> Result<Self, CalculateDailyError>
+    where
+        G: Tabled,
+    {
+        let table = G::SCORE_TABLE;
+        let settings = GuildSettings::get(db_pool, guild_id)
+            .await
+            .unwrap_or_else(|error| {
+                error!(%error, "failed to fetch guild settings, falling back to defaults");
+                GuildSettings::default()
+            });
+
+        let get_scores_string = formatdoc!(
+            "
+            SELECT
+                s.*,
+                RANK() OVER (ORDER BY {order}) AS place
+            FROM
+                {name} s
+                INNER JOIN users u USING (user_id)
+            WHERE
+                s.guild_id = $1
+                AND {board} = $2
+                AND ({on_time})
+                AND ({ranked})
+            ORDER BY place;
+            ",
+            name = table.name,
+            board = table.board,
+            on_time = table.on_time,
+            ranked = table.ranked,
+            order = table.order_by(&settings),
+        );
+        let get_scores = sqlx::query(get_scores_string.as_ref());
+        let entries = match get_scores
+            .bind(guild_id.get() as i64)
+            .bind(board as i32)
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(rows) => {
+                info!("fetched all scores");
+
+                rows.into_iter()
+                    .map(|row| {
+                        let user_id = row.try_get::<i64, _>("user_id")?;
+                        let place = row.try_get::<i64, _>("place")?;
+                        let entry = G::Entry::from_row(&row)?;
+
+                        #[cfg(debug_assertions)]
+                        debug!(user_id, place, ?entry, "got leaderboard entry");
+
+                        Ok(DailyEntry {
+                            user_id: UserId::new(user_id as u64),
+                            place: place as usize,
+                            description: G::describe(&entry),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(CalculateDailyError::FromRow)?
+            }
+            Err(error) => {
+                error!(%error, "failed to fetch all scores");
+                return Err(CalculateDailyError::Unexpected(error));
+            }
+        };
+
+        // Consecutive days have consecutive boards, so the date can be counted back from today's
+        let date = (!G::NUMBERED_BOARDS)
+            .then(|| G::today() - Days::new(G::board_now().saturating_sub(board) as u64));
+
+        Ok(Daily {
+            game: G::display_name(),
+            board,
+            date,
+            today: board == G::board_now(),
+            tiebreak_by_time: table.breaks_ties_by_time(&settings),
+            entries,
+        })
+    }
+}
+
+impl From<Daily> for Listing {
+    fn from(leaderboard: Daily) -> Self {
+        let mut embed = CreateEmbed::new().title(if leaderboard.today {
+            format!("Today's {} Leaderboard", leaderboard.game)
+        } else {
+            format!("{} Leaderboard", leaderboard.game)
+        });
+
+        embed = match leaderboard.date {
+            Some(date) => embed.field("date", format!("{}", date.format("%d %b %Y")), true),
+            None => embed.field("board", format!("{}", leaderboard.board), true),
+        };
+        if leaderboard.tiebreak_by_time {
+            embed = embed.field("Ties broken by", "Earliest submission", true);
+        }
+
+        let mut lines = Vec::new();
+        for entry in leaderboard.entries {
+            lines.push((
+                entry.user_id,
+                format!(
+                    "- {}. {} {}",
+                    entry.place,
+                    Mention::User(entry.user_id),
+                    entry.description,
+                ),
+            ));
+        }
+
+        if leaderboard.today {
+            embed = embed.footer(CreateEmbedFooter::new(
+                "Ranking may change with more submissions! Run `/leaderboard` again to see \
+                 updated scores.",
+            ));
+        }
+
+        Listing::new(embed, lines)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DailyEntry {
+    pub user_id: UserId,
+    pub place: usize,
+
+    /// The score as given by [`Tabled::describe`].
+    pub description: String,
+}
+
+/// Medals awarded on every board of a [`Tabled`] game up to today's.
+#[derive(Clone, Debug)]
+pub struct AllTime {
+    game: &'static str,
+    end_board: usize,
+    include_end: bool,
+    include_late: bool,
+    ranking: RankingStrategy,
+    pub medals_listing: Vec<(UserId, MedalsEntry)>,
+}
+
+impl AllTime {
+    /// Tally the medals over [`Window::all_time`], the same as an all-time [`Overall`] leaderboard
+    /// does for each game, so that the two always agree.
+    pub async fn calculate<G>(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        include_end: bool,
+        include_late: bool,
+    ) -> Result<Self, CalculateAllTimeError>
+    where
+        G: Tabled,
+    {
+        let end_board = G::board_now();
+        let mut window = Window::all_time(G::today(), end_board, include_late);
+        if !include_end {
+            window.last_board = end_board.saturating_sub(1);
+        }
+
+        let Windowed {
+            ranking,
+            medals_listing,
+            ..
+        } = Windowed::calculate::<G>(db_pool, guild_id, window).await?;
+
+        info!(?medals_listing, "medals listing");
+
+        Ok(AllTime {
+            game: G::display_name(),
+            end_board,
+            include_end,
+            include_late,
+            ranking,
+            medals_listing,
+        })
+    }
+}
+
+impl From<AllTime> for Listing {
+    fn from(leaderboard: AllTime) -> Self {
+        let mut embed = CreateEmbed::new()
+            .title(format!("All-Time {} Leaderboard", leaderboard.game))
+            .field(
+                format!("Includes today's board (#{})?", leaderboard.end_board),
+                if leaderboard.include_end { "Yes" } else { "No" },
+                true,
+            )
+            .field(
+                "Includes late submissions?",
+                if leaderboard.include_late {
+                    "Yes"
+                } else {
+                    "No"
+                },
+                true,
+            )
+            .field("Ranked by", leaderboard.ranking.to_string(), true);
+
+        let ranking = leaderboard.ranking;
+        let mut lines = Vec::new();
+        let places = ranking.places(&leaderboard.medals_listing);
+        for (place, (user_id, medals)) in places.into_iter().zip(leaderboard.medals_listing) {
+            lines.push((
+                user_id,
+                format!(
+                    "{}. {}: {}",
+                    place,
+                    Mention::User(user_id),
+                    ranking.describe(&medals),
+                ),
+            ));
+        }
+
+        embed = embed.footer(CreateEmbedFooter::new(
+            "Medals may change with more submissions! Run `/leaderboard` again to see updated \
+             scores.",
+        ));
+
+        Listing::new(embed, lines)
+    }
+}
+
+/// Work out the place of each entry in a list that is already sorted from best to worst, where
+/// `key` gives the value that entries are ranked by. Tied entries share a place, and the places
+/// after them are skipped, in the same way as SQL's `RANK()`: e.g. 1, 2, 2, 4.
+pub fn places<T, K>(entries: &[T], key: impl Fn(&T) -> K) -> Vec<usize>
+where
+    K: PartialEq,
+{
+    places_by(entries, |a, b| key(a) == key(b))
+}
+
+/// Like [`places`], but with `tied` deciding whether two neighbouring entries share a place.
+pub fn places_by<T>(entries: &[T], tied: impl Fn(&T, &T) -> bool) -> Vec<usize> {
+    let mut places = Vec::with_capacity(entries.len());
+
+    for (i, entry) in entries.iter().enumerate() {
+        let place = match (i.checked_sub(1), places.last()) {
+            (Some(previous), Some(&place)) if tied(&entries[previous], entry) => place,
+            _ => i + 1,
+        };
+
+        places.push(place);
+    }
+
+    places
+}
+
+/// A leaderboard that's ready to be shown a page at a time, so that long leaderboards don't run
+/// past Discord's limit on the length of an embed's description.
+#[derive(Clone, Debug)]
+pub struct Listing {
+    /// Everything except the description, which is filled in with the lines for each page.
+    embed: CreateEmbed,
+    lines: Vec<(UserId, String)>,
+}
+
+impl Listing {
+    /// The number of lines shown on each page. Even with long names this keeps well under the
+    /// 4096 characters allowed in a description.
+    pub const PAGE_SIZE: usize = 20;
+
+    /// A leaderboard with `lines` in order from first place, each with the user it's for.
+    pub fn new(embed: CreateEmbed, lines: Vec<(UserId, String)>) -> Self {
+        Self { embed, lines }
+    }
+
+    /// The number of pages, which is at least one even if the leaderboard is empty.
+    pub fn pages(&self) -> usize {
+        self.lines.len().div_ceil(Self::PAGE_SIZE).max(1)
+    }
+
+    /// The page that `user_id` appears on, if they're on the leaderboard at all.
+    pub fn page_of(&self, user_id: UserId) -> Option<usize> {
+        self.lines
+            .iter()
+            .position(|(id, _)| *id == user_id)
+            .map(|index| index / Self::PAGE_SIZE)
+    }
+
+    /// The embed for `page`, counting from zero. Pages past the end show the last page.
+    pub fn page(&self, page: usize) -> CreateEmbed {
+        let page = page.min(self.pages() - 1);
+
+        let mut description = String::new();
+        for (_, line) in self
+            .lines
+            .iter()
+            .skip(page * Self::PAGE_SIZE)
+            .take(Self::PAGE_SIZE)
+        {
+            writeln!(&mut description, "{}", line).expect("should be able to write into String");
+        }
+
+        self.embed.clone().description(description)
+    }
+
+    /// The embed for `page`, with a field showing where `viewer` is on the whole leaderboard, so
+    /// they can see it without finding the page they're on.
+    pub fn page_for(&self, page: usize, viewer: &User) -> CreateEmbed {
+        let position = match self.line_of(viewer.id) {
+            Some(line) => line.trim_start_matches("- ").to_owned(),
+            None => format!("{} isn't on this leaderboard.", Mention::User(viewer.id)),
+        };
+
+        self.page(page).field(
+            format!(
+                "{}'s place",
+                viewer.global_name.as_deref().unwrap_or(&viewer.name)
+            ),
+            position,
+            false,
+        )
+    }
+
+    /// The line for `user_id`, if they're on the leaderboard at all.
+    pub fn line_of(&self, user_id: UserId) -> Option<&str> {
+        self.lines
+            .iter()
+            .find(|(id, _)| *id == user_id)
+            .map(|(_, line)| line.as_str())
+    }
+}
+
+/// A span of consecutive boards to award medals over, e.g. for a monthly competition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Window {
+    pub period: Period,
+    pub first_board: usize,
+    pub last_board: usize,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub include_late: bool,
+}
+
+impl Window {
+    /// The window from the Monday of the week containing `today` up to today's board.
+    pub fn week(today: NaiveDate, board_now: usize, include_late: bool) -> Self {
+        let start = today - Days::new(today.weekday().num_days_from_monday() as u64);
+        Self::up_to_now(Period::Week, start, today, board_now, include_late)
+    }
+
+    /// The window from the first day of the month containing `today` up to today's board.
+    pub fn month(today: NaiveDate, board_now: usize, include_late: bool) -> Self {
+        let start = today.with_day(1).expect("every month has a first day");
+        Self::up_to_now(Period::Month, start, today, board_now, include_late)
+    }
+
+    /// The window of every board up to today's. Games don't all have a date for their first board,
+    /// so this starts at the earliest date there is.
+    pub fn all_time(today: NaiveDate, board_now: usize, include_late: bool) -> Self {
+        Self {
+            period: Period::AllTime,
+            first_board: 0,
+            last_board: board_now,
+            start: NaiveDate::MIN,
+            end: today,
+            include_late,
+        }
+    }
+
+    /// The window for `period` up to today's board, or `None` for [`Period::Range`], which has no
+    /// set start.
+    pub fn current(
+        period: Period,
+        today: NaiveDate,
+        board_now: usize,
+        include_late: bool,
+    ) -> Option<Self> {
+        match period {
+            Period::Week => Some(Self::week(today, board_now, include_late)),
+            Period::Month => Some(Self::month(today, board_now, include_late)),
+            Period::AllTime => Some(Self::all_time(today, board_now, include_late)),
+            Period::Range => None,
+        }
+    }
+
+    fn up_to_now(
+        period: Period,
+        start: NaiveDate,
+        today: NaiveDate,
+        board_now: usize,
+        include_late: bool,
+    ) -> Self {
+        let days_since_start = (today - start).num_days() as usize;
+
+        Self {
+            period,
+            first_board: board_now.saturating_sub(days_since_start),
+            last_board: board_now,
+            start,
+            end: today,
+            include_late,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    Week,
+    Month,
+    Range,
+    AllTime,
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Week => write!(f, "This Week's"),
+            Self::Month => write!(f, "This Month's"),
+            Self::Range => write!(f, "Custom"),
+            Self::AllTime => write!(f, "All-Time"),
+        }
+    }
+}
+
+/// Medals awarded over a [`Window`] of boards, the same way as the all-time leaderboards but with a
+/// lower bound on the boards that count.
+#[derive(Clone, Debug)]
+pub struct Windowed {
+    game: &'static str,
+    window: Window,
+    includes_today: bool,
+    ranking: RankingStrategy,
+    pub medals_listing: Vec<(UserId, MedalsEntry)>,
+}
+
+impl Windowed {
+    pub async fn calculate<G>(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        window: Window,
+    ) -> Result<Self, CalculateAllTimeError>
+    where
+        G: Game,
+    {
+        let settings = GuildSettings::get(db_pool, guild_id)
+            .await
+            .unwrap_or_else(|error| {
+                error!(%error, "failed to fetch guild settings, falling back to defaults");
+                GuildSettings::default()
+            });
+        let ranking = settings.ranking;
+
+        let medals = medals_between::<G>(
+            db_pool,
+            guild_id,
+            &settings,
+            window.first_board,
+            window.last_board,
+            window.include_late,
+        )
+        .await?;
+
+        info!(?medals, "medals table");
+
+        let mut medals_listing: Vec<_> = medals.into_iter().collect();
+        ranking.sort(&mut medals_listing);
+
+        Ok(Windowed {
+            game: G::display_name(),
+            window,
+            includes_today: window.last_board >= G::board_now(),
+            ranking,
+            medals_listing,
+        })
+    }
+}
+
+impl From<Windowed> for Listing {
+    fn from(leaderboard: Windowed) -> Self {
+        let window = leaderboard.window;
+
+        let mut embed = CreateEmbed::new()
+            .title(format!(
+                "{} {} Leaderboard",
+                window.period, leaderboard.game
+            ))
+            .field("From", format!("{}", window.start.format("%d %b %Y")), true)
+            .field("To", format!("{}", window.end.format("%d %b %Y")), true)
+            .field(
+                "Includes late submissions?",
+                if window.include_late { "Yes" } else { "No" },
+                true,
+            )
+            .field("Ranked by", leaderboard.ranking.to_string(), true);
+
+        let ranking = leaderboard.ranking;
+        let mut lines = Vec::new();
+        let places = ranking.places(&leaderboard.medals_listing);
+        for (place, (user_id, medals)) in places.into_iter().zip(leaderboard.medals_listing) {
+            lines.push((
+                user_id,
+                format!(
+                    "{}. {}: {}",
+                    place,
+                    Mention::User(user_id),
+                    ranking.describe(&medals),
+                ),
+            ));
+        }
+
+        if leaderboard.includes_today {
+            embed = embed.footer(CreateEmbedFooter::new(
+                "Medals may change with more submissions! Run `/leaderboard` again to see updated \
+                 scores.",
+            ));
+        }
+
+        Listing::new(embed, lines)
+    }
+}
+
+/// Tally up the medals won by each player on `G`'s boards from `first_board` to `last_board`
+/// inclusive, ranking each board by [`ScoreTable::order_by`](crate::game::ScoreTable::order_by).
+async fn medals_between<G>(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    settings: &GuildSettings,
+    first_board: usize,
+    last_board: usize,
+    include_late: bool,
+) -> Result<HashMap<UserId, MedalsEntry>, CalculateAllTimeError>
+where
+    G: Game,
+{
+    let table = G::SCORE_TABLE;
+
+    let late_clause = if include_late {
+        String::new()
+    } else {
+        format!("AND ({})", table.on_time)
+    };
+
+    let get_scores_string = formatdoc!(
+        "
+        WITH cte AS (
+            SELECT
+                s.user_id,
+                RANK() OVER (
+                    PARTITION BY {board}
+                    ORDER BY {order}
+                ) as place
+            FROM
+                {name} s
+                INNER JOIN users u USING (user_id)
+            WHERE
+                s.guild_id = $1
+                AND {board} BETWEEN $2 AND $3
+                AND ({ranked})
+                {late_clause}
+        )
+        SELECT
+            user_id,
+            place
+        FROM cte
+        ORDER BY place;
+        ",
+        name = table.name,
+        board = table.board,
+        ranked = table.ranked,
+        order = table.order_by(settings),
+        late_clause = late_clause,
+    );
+    let get_scores = sqlx::query(get_scores_string.as_ref());
+    match get_scores
+        .bind(guild_id.get() as i64)
+        .bind(first_board as i32)
+        .bind(last_board as i32)
+        .fetch_all(db_pool)
+        .await
+    {
+        Ok(rows) => {
+            info!(num = %rows.len(), "fetched all scores");
+
+            let rows = rows
+                .into_iter()
+                .map(|row| PlaceQueryRow::from_row(&row).map_err(CalculateAllTimeError::FromRow))
+                .collect::<Result<Vec<_>, CalculateAllTimeError>>()?;
+
+            for row in &rows {
+                debug!(?row, "got row");
+            }
+
+            tally(
+                rows.into_iter()
+                    .map(|row| (UserId::new(row.user_id as u64), row.place)),
+            )
+        }
+        Err(error) => {
+            error!(%error, "failed to fetch all scores");
+            Err(CalculateAllTimeError::Unexpected(error))
+        }
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+struct PlaceQueryRow {
+    user_id: i64,
+    place: i64,
+}
+
+/// Medals awarded across several games over the same [`Period`], added together for each player.
+#[derive(Clone, Debug)]
+pub struct Overall {
+    period: Period,
+    include_late: bool,
+    games: Vec<&'static str>,
+    ranking: RankingStrategy,
+    pub medals_listing: Vec<(UserId, MedalsEntry)>,
+
+    /// The medals each player won in each game, in the order of `games`.
+    pub breakdown: HashMap<UserId, Vec<(&'static str, MedalsEntry)>>,
+}
+
+impl Overall {
+    pub async fn calculate(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        games: &[&'static dyn DynGame],
+        period: Period,
+        include_late: bool,
+    ) -> Result<Self, CalculateAllTimeError> {
+        let ranking = RankingStrategy::for_guild(db_pool, guild_id).await;
+
+        let mut medals = HashMap::<UserId, MedalsEntry>::default();
+        let mut breakdown = HashMap::<UserId, Vec<(&'static str, MedalsEntry)>>::default();
+
+        for game in games {
+            // Each game's day can roll over at a different time, so each has its own window
+            let Some(window) =
+                Window::current(period, game.today(), game.board_now(), include_late)
+            else {
+                continue;
+            };
+
+            let listing = game.windowed_medals(db_pool, guild_id, window).await?;
+            for (user_id, entry) in listing {
+                *medals.entry(user_id).or_default() += entry;
+                breakdown
+                    .entry(user_id)
+                    .or_default()
+                    .push((game.display_name(), entry));
+            }
+        }
+
+        info!(?medals, "overall medals table");
+
+        let mut medals_listing: Vec<_> = medals.into_iter().collect();
+        ranking.sort(&mut medals_listing);
+
+        Ok(Overall {
+            period,
+            include_late,
+            games: games.iter().map(|game| game.display_name()).collect(),
+            ranking,
+            medals_listing,
+            breakdown,
+        })
+    }
+}
+
+impl From<Overall> for Listing {
+    fn from(leaderboard: Overall) -> Self {
+        let embed = CreateEmbed::new()
+            .title(format!("{} Overall Leaderboard", leaderboard.period))
+            .field("Games", leaderboard.games.join(", "), false)
+            .field(
+                "Includes late submissions?",
+                if leaderboard.include_late {
+                    "Yes"
+                } else {
+                    "No"
+                },
+                true,
+            )
+            .field("Ranked by", leaderboard.ranking.to_string(), true)
+            .footer(CreateEmbedFooter::new(
+                "Medals may change with more submissions! Run `/leaderboard` again to see updated \
+                 scores.",
+            ));
+
+        let ranking = leaderboard.ranking;
+        let mut lines = Vec::new();
+        let places = ranking.places(&leaderboard.medals_listing);
+        for (place, (user_id, medals)) in places.into_iter().zip(leaderboard.medals_listing) {
+            let mut line = format!(
+                "{}. {}: {}",
+                place,
+                Mention::User(user_id),
+                ranking.describe(&medals),
+            );
+
+            // Only games with a medal in them, to keep each line short
+            let games = leaderboard
+                .breakdown
+                .get(&user_id)
+                .into_iter()
+                .flatten()
+                .filter(|(_, medals)| medals.medals() > 0)
+                .map(|(game, medals)| format!("{} {}", game, medals))
+                .collect::<Vec<_>>();
+            if !games.is_empty() {
+                write!(&mut line, "\n-# {}", games.join(" · "))
+                    .expect("should be able to write into String");
+            }
+
+            lines.push((user_id, line));
+        }
+
+        Listing::new(embed, lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use serenity::all::{CreateEmbed, UserId};
+
+    use super::{places, tally, Listing, MedalsEntry, Period, RankingStrategy, Window};
+    use crate::{
+        game::{flagle::Flagle, Game},
+        settings::GuildSettings,
+    };
+
+    #[test]
+    fn week_starts_on_monday() {
+        // 17 October 2024 was a Thursday
+        let today = NaiveDate::from_ymd_opt(2024, 10, 17).unwrap();
+        let window = Window::week(today, 100, false);
+
+        assert_eq!(window.period, Period::Week);
+        assert_eq!(window.start, NaiveDate::from_ymd_opt(2024, 10, 14).unwrap());
+        assert_eq!(window.first_board, 97);
+        assert_eq!(window.last_board, 100);
+    }
+
+    #[test]
+    fn week_on_monday_is_one_board() {
+        let today = NaiveDate::from_ymd_opt(2024, 10, 14).unwrap();
+        let window = Window::week(today, 100, false);
+
+        assert_eq!(window.first_board, 100);
+        assert_eq!(window.last_board, 100);
+    }
+
+    #[test]
+    fn month_starts_on_first_day() {
+        let today = NaiveDate::from_ymd_opt(2024, 10, 17).unwrap();
+        let window = Window::month(today, 100, true);
+
+        assert_eq!(window.start, NaiveDate::from_ymd_opt(2024, 10, 1).unwrap());
+        assert_eq!(window.first_board, 84);
+        assert!(window.include_late);
+    }
+
+    #[test]
+    fn window_does_not_start_before_first_board() {
+        let today = NaiveDate::from_ymd_opt(2024, 10, 17).unwrap();
+        let window = Window::month(today, 3, false);
+
+        assert_eq!(window.first_board, 0);
+    }
+
+    #[test]
+    fn places_without_ties_count_up() {
+        assert_eq!(places(&[1, 2, 3], |score| *score), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn tied_entries_share_a_place() {
+        assert_eq!(places(&[1, 2, 2, 3], |score| *score), vec![1, 2, 2, 4]);
+    }
+
+    #[test]
+    fn tie_for_first_skips_second() {
+        assert_eq!(
+            places(&[6, 6, 6, 5, 4], |score| *score),
+            vec![1, 1, 1, 4, 5]
+        );
+    }
+
+    #[test]
+    fn places_use_key_only() {
+        let entries = [("a", 3), ("b", 3), ("c", 1)];
+
+        assert_eq!(places(&entries, |(_, score)| *score), vec![1, 1, 3]);
+    }
+
+    #[test]
+    fn places_of_empty_list_are_empty() {
+        assert!(places(&[] as &[usize], |score| *score).is_empty());
+    }
+
+    fn entry(places: &[usize]) -> MedalsEntry {
+        let mut entry = MedalsEntry::default();
+        for &place in places {
+            entry.record(place);
+        }
+        entry
+    }
+
+    fn ranked(ranking: RankingStrategy, entries: &[MedalsEntry]) -> (Vec<u64>, Vec<usize>) {
+        let mut listing = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (UserId::new(i as u64 + 1), *entry))
+            .collect::<Vec<_>>();
+        ranking.sort(&mut listing);
+
+        let places = ranking.places(&listing);
+        let ids = listing.into_iter().map(|(id, _)| id.get()).collect();
+        (ids, places)
+    }
+
+    #[test]
+    fn tally_counts_medals_and_every_place() {
+        let medals = tally([
+            (UserId::new(1), 1),
+            (UserId::new(1), 5),
+            (UserId::new(2), 3),
+        ])
+        .unwrap();
+
+        assert_eq!(medals[&UserId::new(1)], entry(&[1, 5]));
+        assert_eq!(medals[&UserId::new(1)].gold, 1);
+        assert_eq!(medals[&UserId::new(1)].average_place(), Some(3.0));
+        assert_eq!(medals[&UserId::new(2)].bronze, 1);
+    }
+
+    #[test]
+    fn tally_rejects_places_below_first() {
+        assert!(tally([(UserId::new(1), 0)]).is_err());
+    }
+
+    #[test]
+    fn tied_flagle_scores_share_all_time_places() {
+        // Without the tiebreak, Flagle boards are only ordered by score, so RANK() gives tied
+        // scores the same place
+        assert_eq!(
+            Flagle::SCORE_TABLE.order_by(&GuildSettings::default()),
+            "s.score DESC",
+        );
+
+        // Players 1 and 2 tie for first on both boards, ahead of player 3
+        let medals = tally([
+            (UserId::new(1), 1),
+            (UserId::new(2), 1),
+            (UserId::new(3), 3),
+            (UserId::new(1), 1),
+            (UserId::new(2), 1),
+            (UserId::new(3), 3),
+        ])
+        .unwrap();
+        let ranking = RankingStrategy::default();
+        let mut listing = medals.into_iter().collect::<Vec<_>>();
+        ranking.sort(&mut listing);
+
+        assert_eq!(listing[0].1, entry(&[1, 1]));
+        assert_eq!(listing[1].1, entry(&[1, 1]));
+        assert_eq!(ranking.places(&listing), vec![1, 1, 3]);
+    }
+
+    #[test]
+    fn points_use_the_configured_weights() {
+        // Three bronzes against a silver
+        let entries = [entry(&[2]), entry(&[3, 3, 3])];
+
+        assert_eq!(ranked(RankingStrategy::default(), &entries).0, vec![2, 1]);
+
+        let ranking = RankingStrategy::Points {
+            gold: 10,
+            silver: 5,
+            bronze: 1,
+        };
+        assert_eq!(ranked(ranking, &entries).0, vec![1, 2]);
+    }
+
+    #[test]
+    fn different_medals_with_equal_points_share_a_place() {
+        // One gold is worth the same as two silvers by default
+        let entries = [entry(&[1]), entry(&[2, 2])];
+
+        assert_eq!(ranked(RankingStrategy::default(), &entries).1, vec![1, 1]);
+        assert_eq!(
+            ranked(RankingStrategy::Olympic, &entries),
+            (vec![1, 2], vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn olympic_ranks_golds_first() {
+        let entries = [entry(&[2, 2, 2, 2]), entry(&[1]), entry(&[1, 3])];
+
+        assert_eq!(ranked(RankingStrategy::Olympic, &entries).0, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn total_medals_ignores_colour() {
+        let entries = [entry(&[1]), entry(&[3, 3]), entry(&[2])];
+
+        assert_eq!(
+            ranked(RankingStrategy::TotalMedals, &entries),
+            (vec![2, 1, 3], vec![1, 2, 2])
+        );
+    }
+
+    #[test]
+    fn average_place_counts_boards_without_medals() {
+        // Averages of 2.5, 2 and 2
+        let entries = [entry(&[1, 4]), entry(&[2]), entry(&[1, 3])];
+
+        assert_eq!(
+            ranked(RankingStrategy::AveragePlace, &entries),
+            (vec![3, 2, 1], vec![1, 1, 3])
+        );
+    }
+
+    #[test]
+    fn strategies_round_trip_through_slugs() {
+        for ranking in [
+            RankingStrategy::Points {
+                gold: 5,
+                silver: 3,
+                bronze: 1,
+            },
+            RankingStrategy::Olympic,
+            RankingStrategy::TotalMedals,
+            RankingStrategy::AveragePlace,
+        ] {
+            assert_eq!(
+                RankingStrategy::from_slug(ranking.slug(), (5, 3, 1)),
+                Some(ranking)
+            );
+        }
+    }
+
+    #[test]
+    fn listings_are_split_into_pages() {
+        let lines = (1..=45)
+            .map(|id| (UserId::new(id), format!("line {}", id)))
+            .collect();
+        let listing = Listing::new(CreateEmbed::new(), lines);
+
+        assert_eq!(listing.pages(), 3);
+        assert_eq!(listing.page_of(UserId::new(1)), Some(0));
+        assert_eq!(listing.page_of(UserId::new(20)), Some(0));
+        assert_eq!(listing.page_of(UserId::new(21)), Some(1));
+        assert_eq!(listing.page_of(UserId::new(45)), Some(2));
+        assert_eq!(listing.page_of(UserId::new(46)), None);
+    }
+
+    #[test]
+    fn lines_can_be_found_past_the_first_page() {
+        let lines = (1..=45)
+            .map(|id| (UserId::new(id), format!("line {}", id)))
+            .collect();
+        let listing = Listing::new(CreateEmbed::new(), lines);
+
+        assert_eq!(listing.line_of(UserId::new(42)), Some("line 42"));
+        assert_eq!(listing.line_of(UserId::new(46)), None);
+    }
+
+    #[test]
+    fn empty_listings_have_one_page() {
+        assert_eq!(Listing::new(CreateEmbed::new(), Vec::new()).pages(), 1);
+    }
+
+    #[test]
+    fn medals_add_up_across_games() {
+        let mut total = entry(&[1, 4]);
+        total += entry(&[2, 1]);
+
+        assert_eq!(total, entry(&[1, 4, 2, 1]));
+        assert_eq!(total.gold, 2);
+        assert_eq!(total.average_place(), Some(2.0));
+    }
+
+    #[test]
+    fn ranges_have_no_current_window() {
+        let today = NaiveDate::from_ymd_opt(2024, 10, 17).unwrap();
+
+        assert!(Window::current(Period::Range, today, 100, false).is_none());
+
+        let window = Window::current(Period::AllTime, today, 100, false).unwrap();
+        assert_eq!(window.first_board, 0);
+        assert_eq!(window.last_board, 100);
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+
+use indoc::formatdoc;
+use serenity::all::{CreateEmbed, CreateEmbedFooter, GuildId, Mention, UserId};
+use sqlx::{FromRow, PgPool};
+use tracing::{debug, error, info};
+
+use crate::{
+    game::{
+        leaderboards::{places, Listing, Period, Window},
+        CalculateAllTimeError, DynGame, Game,
+    },
+    settings::GuildSettings,
+};
+
+/// The percentile that each player finished in on each board in `window`, from 100 if nobody beat
+/// them to 0 if everybody did or they failed the board. Boards that only one player played aren't
+/// included, since there's nobody to compare them with.
+pub async fn board_percentiles<G>(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    window: Window,
+) -> Result<Vec<(UserId, f64)>, CalculateAllTimeError>
+where
+    G: Game,
+{
+    let table = G::SCORE_TABLE;
+    let settings = GuildSettings::get(db_pool, guild_id)
+        .await
+        .unwrap_or_else(|error| {
+            error!(%error, "failed to fetch guild settings, falling back to defaults");
+            GuildSettings::default()
+        });
+
+    let late_clause = if window.include_late {
+        String::new()
+    } else {
+        format!("AND ({})", table.on_time)
+    };
+
+    let get_scores_string = formatdoc!(
+        "
+        SELECT
+            s.user_id,
+            ({ranked}) AS ranked,
+            RANK() OVER (
+                PARTITION BY {board}
+                ORDER BY ({ranked}) DESC, {order}
+            ) AS place,
+            COUNT(*) OVER (PARTITION BY {board}) AS players
+        FROM
+            {name} s
+            INNER JOIN users u USING (user_id)
+        WHERE
+            s.guild_id = $1
+            AND {board} BETWEEN $2 AND $3
+            {late_clause}
+        ",
+        name = table.name,
+        board = table.board,
+        ranked = table.ranked,
+        order = table.order_by(&settings),
+        late_clause = late_clause,
+    );
+    let get_scores = sqlx::query(get_scores_string.as_ref());
+    match get_scores
+        .bind(guild_id.get() as i64)
+        .bind(window.first_board as i32)
+        .bind(window.last_board as i32)
+        .fetch_all(db_pool)
+        .await
+    {
+        Ok(rows) => {
+            info!(num = %rows.len(), "fetched all percentiles");
+
+            let mut percentiles = Vec::new();
+            for row in rows {
+                let row =
+                    PercentileQueryRow::from_row(&row).map_err(CalculateAllTimeError::FromRow)?;
+                #[cfg(debug_assertions)]
+                debug!(?row, "got percentile row");
+
+                if let Some(percentile) = percentile(row.place, row.players, row.ranked) {
+                    percentiles.push((UserId::new(row.user_id as u64), percentile));
+                }
+            }
+
+            Ok(percentiles)
+        }
+        Err(error) => {
+            error!(%error, "failed to fetch percentiles");
+            Err(CalculateAllTimeError::Unexpected(error))
+        }
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+struct PercentileQueryRow {
+    user_id: i64,
+    ranked: bool,
+    place: i64,
+    players: i64,
+}
+
+/// The percentile for finishing in `place` out of `players` on a board, the same as Postgres'
+/// `PERCENT_RANK` would give it. Failing a board means finishing last, even if everybody else
+/// failed it too, and a board with nobody else on it has no percentile at all.
+fn percentile(place: i64, players: i64, ranked: bool) -> Option<f64> {
+    if players <= 1 {
+        None
+    } else if !ranked {
+        Some(0.0)
+    } else {
+        Some(100.0 * (1.0 - (place - 1) as f64 / (players - 1) as f64))
+    }
+}
+
+/// A player's percentiles across every board they've played, which unlike medals and raw scores
+/// can be compared between games.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PercentileEntry {
+    pub total: f64,
+    pub boards: usize,
+}
+
+impl PercentileEntry {
+    pub fn average(&self) -> f64 {
+        if self.boards == 0 {
+            0.0
+        } else {
+            self.total / self.boards as f64
+        }
+    }
+}
+
+/// Add up each player's percentiles, and sort them from the highest average to the lowest. Players
+/// with the same average are ordered by who played more boards.
+fn tally(percentiles: impl IntoIterator<Item = (UserId, f64)>) -> Vec<(UserId, PercentileEntry)> {
+    let mut entries = HashMap::<UserId, PercentileEntry>::default();
+    for (user_id, percentile) in percentiles {
+        let entry = entries.entry(user_id).or_default();
+        entry.total += percentile;
+        entry.boards += 1;
+    }
+
+    let mut listing: Vec<_> = entries.into_iter().collect();
+    listing.sort_by(|(a_id, a), (b_id, b)| {
+        b.average()
+            .total_cmp(&a.average())
+            .then_with(|| b.boards.cmp(&a.boards))
+            .then_with(|| a_id.cmp(b_id))
+    });
+
+    listing
+}
+
+/// The average percentile of each player over the same [`Period`] in one or more games.
+#[derive(Clone, Debug)]
+pub struct PercentileIndex {
+    period: Period,
+    include_late: bool,
+    games: Vec<&'static str>,
+    pub listing: Vec<(UserId, PercentileEntry)>,
+}
+
+impl PercentileIndex {
+    pub async fn calculate(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        games: &[&'static dyn DynGame],
+        period: Period,
+        include_late: bool,
+    ) -> Result<Self, CalculateAllTimeError> {
+        let mut percentiles = Vec::new();
+        for game in games {
+            let Some(window) =
+                Window::current(period, game.today(), game.board_now(), include_late)
+            else {
+                continue;
+            };
+
+            percentiles.extend(game.board_percentiles(db_pool, guild_id, window).await?);
+        }
+
+        let listing = tally(percentiles);
+        info!(?listing, "percentile listing");
+
+        Ok(PercentileIndex {
+            period,
+            include_late,
+            games: games.iter().map(|game| game.display_name()).collect(),
+            listing,
+        })
+    }
+}
+
+impl From<PercentileIndex> for Listing {
+    fn from(leaderboard: PercentileIndex) -> Self {
+        let title = match leaderboard.games.as_slice() {
+            [game] => format!("{} {} Percentile Leaderboard", leaderboard.period, game),
+            _ => format!("{} Percentile Leaderboard", leaderboard.period),
+        };
+
+        let embed = CreateEmbed::new()
+            .title(title)
+            .field("Games", leaderboard.games.join(", "), false)
+            .field(
+                "Includes late submissions?",
+                if leaderboard.include_late {
+                    "Yes"
+                } else {
+                    "No"
+                },
+                true,
+            )
+            .footer(CreateEmbedFooter::new(
+                "A percentile of 100 means nobody beat you on a board, and 0 means everyone did. \
+                 Boards with only one player don't count.",
+            ));
+
+        let mut lines = Vec::new();
+        let places = places(&leaderboard.listing, |(_, entry)| {
+            format!("{:.1}", entry.average())
+        });
+        for (place, (user_id, entry)) in places.into_iter().zip(leaderboard.listing) {
+            lines.push((
+                user_id,
+                format!(
+                    "{}. {}: {:.1} ({} {})",
+                    place,
+                    Mention::User(user_id),
+                    entry.average(),
+                    entry.boards,
+                    if entry.boards == 1 { "board" } else { "boards" },
+                ),
+            ));
+        }
+
+        Listing::new(embed, lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::UserId;
+
+    use super::{percentile, tally};
+
+    #[test]
+    fn tally_averages_every_board() {
+        let listing = tally([
+            (UserId::new(1), 100.0),
+            (UserId::new(1), 0.0),
+            (UserId::new(2), 75.0),
+        ]);
+
+        assert_eq!(listing[0].0, UserId::new(2));
+        assert_eq!(listing[0].1.average(), 75.0);
+        assert_eq!(listing[1].0, UserId::new(1));
+        assert_eq!(listing[1].1.average(), 50.0);
+        assert_eq!(listing[1].1.boards, 2);
+    }
+
+    #[test]
+    fn percentiles_count_down_from_first_place() {
+        assert_eq!(percentile(1, 3, true), Some(100.0));
+        assert_eq!(percentile(2, 3, true), Some(50.0));
+        assert_eq!(percentile(3, 3, true), Some(0.0));
+        assert_eq!(percentile(1, 1, true), None);
+    }
+
+    #[test]
+    fn failed_boards_are_the_bottom_percentile() {
+        assert_eq!(percentile(2, 3, false), Some(0.0));
+        assert_eq!(percentile(1, 2, false), Some(0.0));
+        assert_eq!(percentile(1, 1, false), None);
+    }
+
+    #[test]
+    fn failed_boards_lower_the_average() {
+        // The players tied on the first board, then player 1 failed the second and player 2 didn't
+        let listing = tally([
+            (UserId::new(1), percentile(1, 2, true).unwrap()),
+            (UserId::new(1), percentile(2, 2, false).unwrap()),
+            (UserId::new(2), percentile(1, 2, true).unwrap()),
+            (UserId::new(2), percentile(1, 2, true).unwrap()),
+        ]);
+
+        assert_eq!(listing[0].0, UserId::new(2));
+        assert_eq!(listing[0].1.average(), 100.0);
+        assert_eq!(listing[1].0, UserId::new(1));
+        assert_eq!(listing[1].1.average(), 50.0);
+        assert_eq!(listing[1].1.boards, 2);
+    }
+
+    #[test]
+    fn equal_averages_are_ordered_by_boards_played() {
+        let listing = tally([
+            (UserId::new(1), 50.0),
+            (UserId::new(2), 50.0),
+            (UserId::new(2), 50.0),
+        ]);
+
+        assert_eq!(listing[0].0, UserId::new(2));
+        assert_eq!(listing[1].0, UserId::new(1));
+    }
+}
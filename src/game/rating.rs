@@ -0,0 +1,543 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use chrono::{DateTime, Utc};
+use indoc::{formatdoc, indoc};
+use serenity::all::{CreateEmbed, CreateEmbedFooter, GuildId, Mention, UserId};
+use sqlx::{Error as SqlxError, FromRow, PgPool, Postgres, Transaction};
+use thiserror::Error;
+use tracing::{debug, error, info, instrument};
+
+use crate::{
+    game::{
+        leaderboards::{places, Listing},
+        DynGame,
+    },
+    settings::GuildSettings,
+};
+
+/// The rating that every player starts at.
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// The most that a player's rating can change by on a single board.
+const K_FACTOR: f64 = 32.0;
+
+/// Work out everyone's new rating after a board, treating it as a match between every pair of
+/// players who played it. Each player's change is scaled down by the number of opponents, so a
+/// board with many players doesn't swing ratings more than one with few.
+///
+/// `ratings` and `places` are for the same players in the same order. Tied places count as a draw.
+fn rate_board(ratings: &[f64], places: &[usize]) -> Vec<f64> {
+    let opponents = ratings.len().saturating_sub(1);
+    if opponents == 0 {
+        return ratings.to_vec();
+    }
+
+    ratings
+        .iter()
+        .zip(places)
+        .enumerate()
+        .map(|(i, (&rating, &place))| {
+            let difference = ratings
+                .iter()
+                .zip(places)
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, (&other_rating, &other_place))| {
+                    let expected = 1.0 / (1.0 + 10f64.powf((other_rating - rating) / 400.0));
+                    let actual = match place.cmp(&other_place) {
+                        Ordering::Less => 1.0,
+                        Ordering::Equal => 0.5,
+                        Ordering::Greater => 0.0,
+                    };
+                    actual - expected
+                })
+                .sum::<f64>();
+
+            rating + K_FACTOR * difference / opponents as f64
+        })
+        .collect()
+}
+
+/// Count every board of `game` that has closed since the ratings in `guild_id` were last updated.
+#[instrument(skip_all, fields(%guild_id, game = game.slug()))]
+pub async fn update(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    game: &dyn DynGame,
+) -> Result<(), RatingError> {
+    let settings = settings_for(db_pool, guild_id).await;
+
+    let mut txn = db_pool.begin().await.map_err(RatingError::BeginTxn)?;
+    apply_closed_boards(&mut txn, guild_id, game, &settings).await?;
+    txn.commit().await.map_err(RatingError::CommitTxn)
+}
+
+/// Throw away the ratings for `game` in `guild_id` and work them out again from every closed board,
+/// e.g. because scores for boards that have already been counted were imported or corrected.
+#[instrument(skip_all, fields(%guild_id, game = game.slug()))]
+pub async fn recompute(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    game: &dyn DynGame,
+) -> Result<(), RatingError> {
+    let settings = settings_for(db_pool, guild_id).await;
+
+    let mut txn = db_pool.begin().await.map_err(RatingError::BeginTxn)?;
+
+    for table in ["ratings", "rating_progress"] {
+        let delete_string = formatdoc!(
+            "
+            DELETE FROM {table}
+            WHERE
+                guild_id = $1
+                AND game = $2;
+            ",
+            table = table,
+        );
+        if let Err(error) = sqlx::query(delete_string.as_ref())
+            .bind(guild_id.get() as i64)
+            .bind(game.slug())
+            .execute(txn.as_mut())
+            .await
+        {
+            error!(%error, table, "failed to clear ratings");
+            return Err(RatingError::Unexpected(error));
+        }
+    }
+
+    apply_closed_boards(&mut txn, guild_id, game, &settings).await?;
+    txn.commit().await.map_err(RatingError::CommitTxn)?;
+
+    info!("recomputed ratings");
+    Ok(())
+}
+
+/// Recompute the ratings for `game` in `guild_id` if `board` has already closed, since a change to
+/// a score on it might have changed everything after it. Failures are logged rather than returned,
+/// since the ratings will just be a little out of date.
+pub async fn board_changed(db_pool: &PgPool, guild_id: GuildId, game: &dyn DynGame, board: usize) {
+    if board >= game.board_now() {
+        return;
+    }
+
+    if let Err(error) = recompute(db_pool, guild_id, game).await {
+        error!(%error, %guild_id, game = game.slug(), "failed to recompute ratings");
+    }
+}
+
+async fn settings_for(db_pool: &PgPool, guild_id: GuildId) -> GuildSettings {
+    GuildSettings::get(db_pool, guild_id)
+        .await
+        .unwrap_or_else(|error| {
+            error!(%error, "failed to fetch guild settings, falling back to defaults");
+            GuildSettings::default()
+        })
+}
+
+async fn apply_closed_boards(
+    txn: &mut Transaction<'_, Postgres>,
+    guild_id: GuildId,
+    game: &dyn DynGame,
+    settings: &GuildSettings,
+) -> Result<(), RatingError> {
+    let Some(last_closed) = game.board_now().checked_sub(1) else {
+        return Ok(());
+    };
+
+    let get_progress = sqlx::query_scalar::<_, i32>(indoc! {"
+        SELECT last_board
+        FROM rating_progress
+        WHERE
+            guild_id = $1
+            AND game = $2
+        FOR UPDATE;
+    "});
+    let last_rated = match get_progress
+        .bind(guild_id.get() as i64)
+        .bind(game.slug())
+        .fetch_optional(txn.as_mut())
+        .await
+    {
+        Ok(last_rated) => last_rated.unwrap_or(-1),
+        Err(error) => {
+            error!(%error, "failed to fetch rating progress");
+            return Err(RatingError::Unexpected(error));
+        }
+    };
+
+    if last_rated >= last_closed as i32 {
+        debug!(last_rated, "ratings are up to date");
+        return Ok(());
+    }
+
+    let mut ratings = fetch_ratings(txn.as_mut(), guild_id, game).await?;
+
+    // Only on-time scores are counted, the same as the daily leaderboards
+    let table = game.score_table();
+    let tiebreak_by_time = table.breaks_ties_by_time(settings);
+    let get_places_string = formatdoc!(
+        "
+        SELECT
+            ({board})::INTEGER AS board,
+            s.user_id,
+            ({score})::FLOAT8 AS score,
+            s.submitted_at
+        FROM
+            {name} s
+            INNER JOIN users u USING (user_id)
+        WHERE
+            s.guild_id = $1
+            AND {board} > $2
+            AND {board} <= $3
+            AND ({on_time})
+            AND ({ranked})
+        ORDER BY 1, {order};
+        ",
+        name = table.name,
+        board = table.board,
+        score = table.score,
+        on_time = table.on_time,
+        ranked = table.ranked,
+        order = table.order_by(settings),
+    );
+    let rows = match sqlx::query(get_places_string.as_ref())
+        .bind(guild_id.get() as i64)
+        .bind(last_rated)
+        .bind(last_closed as i32)
+        .fetch_all(txn.as_mut())
+        .await
+    {
+        Ok(rows) => rows
+            .iter()
+            .map(PlaceQueryRow::from_row)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RatingError::FromRow)?,
+        Err(error) => {
+            error!(%error, "failed to fetch places");
+            return Err(RatingError::Unexpected(error));
+        }
+    };
+
+    info!(num = rows.len(), last_rated, last_closed, "rating boards");
+
+    for board in rows.chunk_by(|a, b| a.board == b.board) {
+        // A board that only one player finished has nobody to be compared with
+        if board.len() < 2 {
+            continue;
+        }
+
+        let players = board
+            .iter()
+            .map(|row| UserId::new(row.user_id as u64))
+            .collect::<Vec<_>>();
+        let places = board_places(board, tiebreak_by_time);
+        let before = players
+            .iter()
+            .map(|user_id| {
+                ratings
+                    .get(user_id)
+                    .map_or(INITIAL_RATING, |entry| entry.rating)
+            })
+            .collect::<Vec<_>>();
+
+        for (user_id, rating) in players.into_iter().zip(rate_board(&before, &places)) {
+            let entry = ratings.entry(user_id).or_default();
+            entry.rating = rating;
+            entry.boards += 1;
+        }
+    }
+
+    let (user_ids, entries): (Vec<_>, Vec<_>) = ratings.into_iter().unzip();
+    let upsert_ratings = sqlx::query(indoc! {"
+        INSERT INTO ratings (guild_id, game, user_id, rating, boards)
+        SELECT $1, $2, r.*
+        FROM UNNEST($3::BIGINT[], $4::FLOAT8[], $5::INTEGER[]) r
+        ON CONFLICT (guild_id, game, user_id) DO UPDATE
+        SET
+            rating = EXCLUDED.rating,
+            boards = EXCLUDED.boards;
+    "});
+    if let Err(error) = upsert_ratings
+        .bind(guild_id.get() as i64)
+        .bind(game.slug())
+        .bind(
+            user_ids
+                .iter()
+                .map(|user_id| user_id.get() as i64)
+                .collect::<Vec<_>>(),
+        )
+        .bind(entries.iter().map(|entry| entry.rating).collect::<Vec<_>>())
+        .bind(
+            entries
+                .iter()
+                .map(|entry| entry.boards as i32)
+                .collect::<Vec<_>>(),
+        )
+        .execute(txn.as_mut())
+        .await
+    {
+        error!(%error, "failed to save ratings");
+        return Err(RatingError::Unexpected(error));
+    }
+
+    let upsert_progress = sqlx::query(indoc! {"
+        INSERT INTO rating_progress (guild_id, game, last_board)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (guild_id, game) DO UPDATE
+        SET last_board = EXCLUDED.last_board;
+    "});
+    if let Err(error) = upsert_progress
+        .bind(guild_id.get() as i64)
+        .bind(game.slug())
+        .bind(last_closed as i32)
+        .execute(txn.as_mut())
+        .await
+    {
+        error!(%error, "failed to save rating progress");
+        return Err(RatingError::Unexpected(error));
+    }
+
+    Ok(())
+}
+
+async fn fetch_ratings(
+    executor: impl sqlx::PgExecutor<'_>,
+    guild_id: GuildId,
+    game: &dyn DynGame,
+) -> Result<HashMap<UserId, RatingEntry>, RatingError> {
+    let get_ratings = sqlx::query(indoc! {"
+        SELECT
+            user_id,
+            rating,
+            boards
+        FROM ratings
+        WHERE
+            guild_id = $1
+            AND game = $2;
+    "});
+    match get_ratings
+        .bind(guild_id.get() as i64)
+        .bind(game.slug())
+        .fetch_all(executor)
+        .await
+    {
+        Ok(rows) => rows
+            .iter()
+            .map(|row| {
+                RatingQueryRow::from_row(row)
+                    .map(|row| (UserId::new(row.user_id as u64), row.into()))
+                    .map_err(RatingError::FromRow)
+            })
+            .collect(),
+        Err(error) => {
+            error!(%error, "failed to fetch ratings");
+            Err(RatingError::Unexpected(error))
+        }
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+struct PlaceQueryRow {
+    board: i32,
+    user_id: i64,
+    score: f64,
+    submitted_at: Option<DateTime<Utc>>,
+}
+
+/// The place of each score on a board, which are sorted from best to worst. With
+/// `tiebreak_by_time`, tied scores are placed by who submitted first, the same as on the daily
+/// leaderboards.
+fn board_places(board: &[PlaceQueryRow], tiebreak_by_time: bool) -> Vec<usize> {
+    places(board, |row| {
+        (row.score, tiebreak_by_time.then_some(row.submitted_at))
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RatingEntry {
+    pub rating: f64,
+
+    /// The number of boards that have counted towards the rating.
+    pub boards: usize,
+}
+
+impl Default for RatingEntry {
+    fn default() -> Self {
+        Self {
+            rating: INITIAL_RATING,
+            boards: 0,
+        }
+    }
+}
+
+impl From<RatingQueryRow> for RatingEntry {
+    fn from(row: RatingQueryRow) -> Self {
+        Self {
+            rating: row.rating,
+            boards: row.boards as usize,
+        }
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+struct RatingQueryRow {
+    user_id: i64,
+    rating: f64,
+    boards: i32,
+}
+
+/// Every player's current rating for one game.
+#[derive(Clone, Debug)]
+pub struct Ratings {
+    game: &'static str,
+    pub listing: Vec<(UserId, RatingEntry)>,
+}
+
+impl Ratings {
+    /// Bring the ratings up to date with any boards that have closed, and then fetch them.
+    pub async fn calculate(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        game: &dyn DynGame,
+    ) -> Result<Self, RatingError> {
+        update(db_pool, guild_id, game).await?;
+
+        let mut listing: Vec<_> = fetch_ratings(db_pool, guild_id, game)
+            .await?
+            .into_iter()
+            .collect();
+        listing.sort_by(|(a_id, a), (b_id, b)| {
+            b.rating
+                .total_cmp(&a.rating)
+                .then_with(|| b.boards.cmp(&a.boards))
+                .then_with(|| a_id.cmp(b_id))
+        });
+
+        Ok(Ratings {
+            game: game.display_name(),
+            listing,
+        })
+    }
+}
+
+impl From<Ratings> for Listing {
+    fn from(leaderboard: Ratings) -> Self {
+        let embed = CreateEmbed::new()
+            .title(format!("{} Ratings", leaderboard.game))
+            .footer(CreateEmbedFooter::new(format!(
+                "Everyone starts at {}, and ratings change after each board closes depending on \
+                 who you beat and who beat you.",
+                INITIAL_RATING,
+            )));
+
+        let mut lines = Vec::new();
+        let places = places(&leaderboard.listing, |(_, entry)| {
+            entry.rating.round() as i64
+        });
+        for (place, (user_id, entry)) in places.into_iter().zip(leaderboard.listing) {
+            lines.push((
+                user_id,
+                format!(
+                    "{}. {}: {:.0} ({} {})",
+                    place,
+                    Mention::User(user_id),
+                    entry.rating,
+                    entry.boards,
+                    if entry.boards == 1 { "board" } else { "boards" },
+                ),
+            ));
+        }
+
+        Listing::new(embed, lines)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RatingError {
+    #[error("failed to begin transaction: {0}")]
+    BeginTxn(#[source] SqlxError),
+
+    #[error("failed to commit transaction: {0}")]
+    CommitTxn(#[source] SqlxError),
+
+    #[error("failed to extract data from row: {0}")]
+    FromRow(#[source] SqlxError),
+
+    #[error("unexpected SQLx error: {0}")]
+    Unexpected(SqlxError),
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{board_places, rate_board, PlaceQueryRow, INITIAL_RATING};
+    use crate::{
+        game::{flagle::Flagle, Game},
+        settings::GuildSettings,
+    };
+
+    #[test]
+    fn winners_gain_what_losers_lose() {
+        let after = rate_board(&[INITIAL_RATING; 3], &[1, 2, 3]);
+
+        assert!(after[0] > INITIAL_RATING);
+        assert_eq!(after[1], INITIAL_RATING);
+        assert!(after[2] < INITIAL_RATING);
+        assert!((after.iter().sum::<f64>() - 3.0 * INITIAL_RATING).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ties_between_equal_players_change_nothing() {
+        assert_eq!(
+            rate_board(&[INITIAL_RATING; 2], &[1, 1]),
+            vec![INITIAL_RATING; 2]
+        );
+    }
+
+    #[test]
+    fn beating_a_stronger_player_is_worth_more() {
+        let upset = rate_board(&[1400.0, 1600.0], &[1, 2]);
+        let expected = rate_board(&[1600.0, 1400.0], &[1, 2]);
+
+        assert!(upset[0] - 1400.0 > expected[0] - 1600.0);
+    }
+
+    #[test]
+    fn playing_alone_changes_nothing() {
+        assert_eq!(rate_board(&[1234.0], &[1]), vec![1234.0]);
+    }
+
+    #[test]
+    fn tied_flagle_scores_are_broken_by_time_when_turned_on() {
+        let board = [1, 2].map(|user_id| PlaceQueryRow {
+            board: 1,
+            user_id,
+            score: 6.0,
+            submitted_at: Some(
+                Utc.with_ymd_and_hms(2024, 10, 17, 12, user_id as u32, 0)
+                    .unwrap(),
+            ),
+        });
+        let tiebreak = GuildSettings {
+            flagle_tiebreak_by_time: true,
+            ..GuildSettings::default()
+        };
+
+        let places = board_places(&board, Flagle::SCORE_TABLE.breaks_ties_by_time(&tiebreak));
+        assert_eq!(places, vec![1, 2]);
+        let after = rate_board(&[INITIAL_RATING; 2], &places);
+        assert!(after[0] > INITIAL_RATING);
+        assert!(after[1] < INITIAL_RATING);
+
+        let places = board_places(
+            &board,
+            Flagle::SCORE_TABLE.breaks_ties_by_time(&GuildSettings::default()),
+        );
+        assert_eq!(places, vec![1, 1]);
+        assert_eq!(
+            rate_board(&[INITIAL_RATING; 2], &places),
+            vec![INITIAL_RATING; 2]
+        );
+    }
+}
@@ -0,0 +1,345 @@
+use indoc::formatdoc;
+use serenity::all::{CreateEmbed, GuildId, Mention, UserId};
+use sqlx::{FromRow, PgPool};
+use tracing::{debug, error, info};
+
+use crate::{
+    game::{
+        leaderboards::{MedalsEntry, RankingStrategy},
+        CalculateStatsError, Game,
+    },
+    settings::GuildSettings,
+};
+
+/// A single player's history for one game in one guild.
+#[derive(Clone, Debug)]
+pub struct Stats {
+    game: &'static str,
+    user_id: UserId,
+    pub played: usize,
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    pub average_score: Option<f64>,
+    pub best_score: Option<f64>,
+    pub medals: MedalsEntry,
+    ranking: RankingStrategy,
+}
+
+impl Stats {
+    pub async fn calculate<G>(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Self, CalculateStatsError>
+    where
+        G: Game,
+    {
+        let table = G::SCORE_TABLE;
+        let settings = GuildSettings::get(db_pool, guild_id)
+            .await
+            .unwrap_or_else(|error| {
+                error!(%error, "failed to fetch guild settings, falling back to defaults");
+                GuildSettings::default()
+            });
+
+        // Places are only awarded for on-time scores, the same as the daily leaderboards
+        let get_scores_string = formatdoc!(
+            "
+            WITH places AS (
+                SELECT
+                    s.user_id,
+                    {board} AS board,
+                    RANK() OVER (
+                        PARTITION BY {board}
+                        ORDER BY {order}
+                    ) AS place
+                FROM
+                    {name} s
+                    INNER JOIN users u USING (user_id)
+                WHERE
+                    s.guild_id = $1
+                    AND ({on_time})
+                    AND ({ranked})
+            )
+            SELECT
+                ({board})::INTEGER AS board,
+                ({on_time}) AS on_time,
+                ({ranked}) AS ranked,
+                ({score})::FLOAT8 AS score,
+                p.place
+            FROM
+                {name} s
+                LEFT JOIN places p ON p.user_id = s.user_id AND p.board = {board}
+            WHERE
+                s.guild_id = $1
+                AND s.user_id = $2
+            ORDER BY 1;
+            ",
+            name = table.name,
+            board = table.board,
+            on_time = table.on_time,
+            ranked = table.ranked,
+            score = table.score,
+            order = table.order_by(&settings),
+        );
+        let get_scores = sqlx::query(get_scores_string.as_ref());
+        let rows = match get_scores
+            .bind(guild_id.get() as i64)
+            .bind(user_id.get() as i64)
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(rows) => {
+                info!(num = %rows.len(), "fetched all scores for user");
+
+                rows.into_iter()
+                    .map(|row| {
+                        StatsQueryRow::from_row(&row)
+                            .map(|row| {
+                                #[cfg(debug_assertions)]
+                                debug!(?row, "got stats row");
+                                row
+                            })
+                            .map_err(CalculateStatsError::FromRow)
+                    })
+                    .collect::<Result<Vec<_>, CalculateStatsError>>()?
+            }
+            Err(error) => {
+                error!(%error, "failed to fetch scores for user");
+                return Err(CalculateStatsError::Unexpected(error));
+            }
+        };
+
+        Ok(Stats::from_rows(
+            G::display_name(),
+            user_id,
+            &rows,
+            G::board_now() as i32,
+            table.higher_is_better,
+            settings.ranking,
+        ))
+    }
+
+    fn from_rows(
+        game: &'static str,
+        user_id: UserId,
+        rows: &[StatsQueryRow],
+        board_now: i32,
+        higher_is_better: bool,
+        ranking: RankingStrategy,
+    ) -> Self {
+        let mut on_time_boards = rows
+            .iter()
+            .filter(|row| row.on_time)
+            .map(|row| row.board)
+            .collect::<Vec<_>>();
+        on_time_boards.sort_unstable();
+        on_time_boards.dedup();
+
+        let (current_streak, longest_streak) = streaks(&on_time_boards, board_now);
+
+        let ranked_scores = rows
+            .iter()
+            .filter(|row| row.ranked)
+            .filter_map(|row| row.score)
+            .collect::<Vec<_>>();
+
+        let average_score = (!ranked_scores.is_empty())
+            .then(|| ranked_scores.iter().sum::<f64>() / ranked_scores.len() as f64);
+        let best_score = ranked_scores.iter().copied().reduce(|best, score| {
+            if higher_is_better {
+                best.max(score)
+            } else {
+                best.min(score)
+            }
+        });
+
+        let mut medals = MedalsEntry::default();
+        for place in rows.iter().filter_map(|row| row.place) {
+            if let Ok(place @ 1..) = usize::try_from(place) {
+                medals.record(place);
+            }
+        }
+
+        Stats {
+            game,
+            user_id,
+            played: rows.len(),
+            current_streak,
+            longest_streak,
+            average_score,
+            best_score,
+            medals,
+            ranking,
+        }
+    }
+}
+
+/// Calculate the current and longest runs of consecutive boards in `boards`, which must be sorted
+/// and free of duplicates. A run that ends on yesterday's board is still current, since there's
+/// time left to play today's.
+fn streaks(boards: &[i32], board_now: i32) -> (usize, usize) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut last = None;
+
+    for &board in boards {
+        run = match last {
+            Some(last) if board == last + 1 => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        last = Some(board);
+    }
+
+    let current = match last {
+        Some(last) if last >= board_now - 1 => run,
+        _ => 0,
+    };
+
+    (current, longest)
+}
+
+/// Format a score without a trailing ".0" for games with whole-number scores.
+fn format_score(score: f64) -> String {
+    if score.fract() == 0.0 {
+        format!("{}", score as i64)
+    } else {
+        format!("{:.1}", score)
+    }
+}
+
+impl From<Stats> for CreateEmbed {
+    fn from(stats: Stats) -> Self {
+        let embed = CreateEmbed::new().title(format!("{} Stats", stats.game));
+
+        if stats.played == 0 {
+            return embed.description(format!(
+                "{} hasn't played {} in this server yet!",
+                Mention::User(stats.user_id),
+                stats.game,
+            ));
+        }
+
+        embed
+            .description(format!("Stats for {}", Mention::User(stats.user_id)))
+            .field("Games played", format!("{}", stats.played), true)
+            .field("Current streak", format!("{}", stats.current_streak), true)
+            .field("Longest streak", format!("{}", stats.longest_streak), true)
+            .field(
+                "Average score",
+                stats
+                    .average_score
+                    .map(|average| format!("{:.2}", average))
+                    .unwrap_or_else(|| "-".to_owned()),
+                true,
+            )
+            .field(
+                "Best score",
+                stats
+                    .best_score
+                    .map(format_score)
+                    .unwrap_or_else(|| "-".to_owned()),
+                true,
+            )
+            .field("Medals", stats.ranking.describe(&stats.medals), false)
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+struct StatsQueryRow {
+    board: i32,
+    on_time: bool,
+    ranked: bool,
+    score: Option<f64>,
+    place: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::UserId;
+
+    use super::{streaks, RankingStrategy, Stats, StatsQueryRow};
+
+    fn from_rows(rows: &[StatsQueryRow], board_now: i32, higher_is_better: bool) -> Stats {
+        Stats::from_rows(
+            "Test",
+            UserId::new(1),
+            rows,
+            board_now,
+            higher_is_better,
+            RankingStrategy::default(),
+        )
+    }
+
+    fn row(board: i32, on_time: bool, score: f64, place: Option<i64>) -> StatsQueryRow {
+        StatsQueryRow {
+            board,
+            on_time,
+            ranked: true,
+            score: Some(score),
+            place,
+        }
+    }
+
+    #[test]
+    fn streak_ending_today_is_current() {
+        assert_eq!(streaks(&[1, 2, 3, 7, 8], 8), (2, 3));
+    }
+
+    #[test]
+    fn streak_ending_yesterday_is_current() {
+        assert_eq!(streaks(&[5, 6, 7], 8), (3, 3));
+    }
+
+    #[test]
+    fn streak_ending_before_yesterday_is_broken() {
+        assert_eq!(streaks(&[5, 6], 8), (0, 2));
+    }
+
+    #[test]
+    fn no_boards_means_no_streaks() {
+        assert_eq!(streaks(&[], 8), (0, 0));
+    }
+
+    #[test]
+    fn late_scores_count_as_played_but_not_towards_streaks() {
+        let rows = [
+            row(1, true, 4.0, Some(1)),
+            row(2, false, 2.0, None),
+            row(3, true, 6.0, Some(3)),
+        ];
+
+        let stats = from_rows(&rows, 3, false);
+
+        assert_eq!(stats.played, 3);
+        assert_eq!(stats.current_streak, 1);
+        assert_eq!(stats.longest_streak, 1);
+        assert_eq!(stats.average_score, Some(4.0));
+        assert_eq!(stats.best_score, Some(2.0));
+        assert_eq!(stats.medals.gold, 1);
+        assert_eq!(stats.medals.silver, 0);
+        assert_eq!(stats.medals.bronze, 1);
+    }
+
+    #[test]
+    fn best_score_respects_direction() {
+        let rows = [row(1, true, 4.0, None), row(2, true, 6.0, None)];
+
+        assert_eq!(from_rows(&rows, 2, true).best_score, Some(6.0));
+        assert_eq!(from_rows(&rows, 2, false).best_score, Some(4.0));
+    }
+
+    #[test]
+    fn unranked_scores_are_left_out_of_averages() {
+        let mut failed = row(2, true, 7.0, None);
+        failed.ranked = false;
+        let rows = [row(1, true, 3.0, None), failed];
+
+        let stats = from_rows(&rows, 2, false);
+
+        assert_eq!(stats.played, 2);
+        assert_eq!(stats.current_streak, 2);
+        assert_eq!(stats.average_score, Some(3.0));
+    }
+}
@@ -0,0 +1,518 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use indoc::indoc;
+use serenity::{
+    all::UserId,
+    model::prelude::{GuildId, User},
+};
+use sqlx::{postgres::PgRow, Error as SqlxError, FromRow, PgPool, Row as _};
+use thiserror::Error;
+use tracing::{debug, error, info};
+
+use super::{
+    CalculateAllTimeError, CalculateDailyError, ScoreInsertionError, ScoreTable, Submission,
+};
+use crate::{
+    game::leaderboards::{AllTime, Daily},
+    persist::{insert_guild_user, GuildUserRow, InsertionTarget, UserRow},
+};
+
+pub mod leaderboards;
+pub mod utils;
+
+pub struct Travle;
+
+impl super::Game for Travle {
+    type Score = Score;
+    type DailyLeaderboard = Daily;
+    type AllTimeLeaderboard = AllTime;
+
+    fn slug() -> &'static str {
+        "travle"
+    }
+
+    fn display_name() -> &'static str {
+        "Travle"
+    }
+
+    const SCORE_TABLE: ScoreTable = ScoreTable {
+        name: "travle_scores",
+        board: "s.board",
+        on_time: "s.board = s.day_added",
+        score: "s.extra_guesses",
+        ranked: "s.extra_guesses IS NOT NULL",
+        higher_is_better: false,
+        time_tiebreak: false,
+    };
+
+    fn board_now() -> usize {
+        utils::board_now()
+    }
+
+    fn today() -> NaiveDate {
+        utils::today()
+    }
+
+    fn board_on_date(date: NaiveDate) -> Option<usize> {
+        utils::board_on_date(date)
+    }
+
+    async fn daily_leaderboard(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        board: usize,
+    ) -> Result<Daily, CalculateDailyError> {
+        Daily::calculate::<Self>(db_pool, guild_id, board).await
+    }
+
+    async fn all_time_leaderboard(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        include_today: bool,
+        include_late: bool,
+    ) -> Result<AllTime, CalculateAllTimeError> {
+        AllTime::calculate::<Self>(db_pool, guild_id, include_today, include_late).await
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Score {
+    pub guesses: usize,
+    /// How many more guesses were taken than the shortest possible route needed, or `None` if the
+    /// route wasn't completed.
+    pub extra_guesses: Option<usize>,
+    pub board: usize,
+}
+
+impl FromStr for Score {
+    type Err = ParseScoreError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let mut lines = raw.trim().lines();
+
+        let details = lines
+            .next()
+            .ok_or(ParseScoreError::Empty)?
+            .trim()
+            .strip_prefix("#travle #")
+            .ok_or(ParseScoreError::Missing(Section::Details))?;
+
+        let (board_str, result) = details
+            .split_once(' ')
+            .ok_or(ParseScoreError::Missing(Section::Result))?;
+
+        let board = board_str
+            .parse::<usize>()
+            .map_err(|_| ParseScoreError::NotANumber(Number::Board))?;
+
+        // A completed route is shown as "+N", optionally followed by a note like "(Perfect)" or
+        // "(1 hint)", while a failed one is shown as "(N away)".
+        let extra_guesses = if let Some(extra) = result.strip_prefix('+') {
+            let extra_str = extra.split_whitespace().next().unwrap_or_default();
+            Some(
+                extra_str
+                    .parse::<usize>()
+                    .map_err(|_| ParseScoreError::NotANumber(Number::ExtraGuesses))?,
+            )
+        } else if result.starts_with('(') && result.ends_with(" away)") {
+            None
+        } else {
+            return Err(ParseScoreError::InvalidFormat(Section::Result));
+        };
+
+        let guesses = lines
+            .next()
+            .ok_or(ParseScoreError::Missing(Section::Grid))?
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| match c {
+                '✅' | '🟩' | '🟧' | '🟥' | '⬛' => Ok(()),
+                _ => Err(ParseScoreError::InvalidFormat(Section::Grid)),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .len();
+
+        if guesses == 0 {
+            return Err(ParseScoreError::Missing(Section::Grid));
+        }
+
+        // Every route needs at least one guess, so the extra guesses can never account for all of
+        // them
+        if extra_guesses.is_some_and(|extra| extra >= guesses) {
+            return Err(ParseScoreError::Inconsistent);
+        }
+
+        Ok(Score {
+            guesses,
+            extra_guesses,
+            board,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum ParseScoreError {
+    #[error("string is empty")]
+    Empty,
+
+    #[error("string does not contain a {0}")]
+    Missing(Section),
+
+    #[error("{0} was not formatted as expected")]
+    InvalidFormat(Section),
+
+    #[error("{0} is not a number")]
+    NotANumber(Number),
+
+    #[error("extra guesses and grid don't match")]
+    Inconsistent,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Section {
+    Details,
+    Result,
+    Grid,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Section::Details => write!(f, "details line"),
+            Section::Result => write!(f, "result"),
+            Section::Grid => write!(f, "grid section"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Number {
+    Board,
+    ExtraGuesses,
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Board => write!(f, "board number"),
+            Number::ExtraGuesses => write!(f, "extra guess count"),
+        }
+    }
+}
+
+impl super::Score for Score {
+    type Game = Travle;
+
+    fn is_recorded_in(&self, row: &PgRow) -> Result<bool, SqlxError> {
+        Ok(ScoreRow::from_row(row)?.records(self))
+    }
+
+    async fn insert(
+        self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        user: &User,
+        submission: Submission,
+    ) -> Result<impl super::InsertedScore, ScoreInsertionError> {
+        let mut txn = db_pool
+            .begin()
+            .await
+            .map_err(ScoreInsertionError::BeginTxn)?;
+
+        insert_guild_user(&mut txn, guild_id, user).await?;
+
+        let score_row = ScoreRow::from_score_at_opt(self, guild_id, user.id, submission)
+            .ok_or(ScoreInsertionError::BeforeFirstBoard)?;
+
+        let get_best_score = sqlx::query(indoc! {"
+            SELECT extra_guesses FROM travle_scores
+            WHERE
+                guild_id = $1
+                AND user_id != $2
+                AND board = $3
+                AND board = day_added
+                AND extra_guesses IS NOT NULL
+            ORDER BY extra_guesses ASC
+            LIMIT 1;
+        "});
+        let best_so_far = match get_best_score
+            .bind(guild_id.get() as i64)
+            .bind(user.id.get() as i64)
+            .bind(score_row.board)
+            .fetch_one(txn.as_mut())
+            .await
+            .and_then(|row| row.try_get::<i32, _>(0))
+        {
+            Ok(best_extra_guesses) => {
+                info!(
+                    %best_extra_guesses,
+                    board = %score_row.board,
+                    "got best existing score for this board"
+                );
+
+                score_row
+                    .extra_guesses
+                    .is_some_and(|extra| extra <= best_extra_guesses)
+            }
+            Err(SqlxError::RowNotFound) => {
+                info!(
+                    board = %score_row.board,
+                    "there are no on-time scores for this board"
+                );
+                score_row.extra_guesses.is_some()
+            }
+            Err(error) => {
+                error!(
+                    %error,
+                    board = %score_row.board,
+                    "failed to get current best score for this board"
+                );
+                true
+            }
+        };
+
+        let insert_score = sqlx::query(indoc! {"
+            INSERT INTO travle_scores (
+                guild_id,
+                user_id,
+                guesses,
+                extra_guesses,
+                board,
+                day_added,
+                message_id,
+                channel_id,
+                submitted_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9);
+        "});
+        match insert_score
+            .bind(score_row.guild_id)
+            .bind(score_row.user_id)
+            .bind(score_row.guesses)
+            .bind(score_row.extra_guesses)
+            .bind(score_row.board)
+            .bind(score_row.day_added)
+            .bind(score_row.message_id)
+            .bind(score_row.channel_id)
+            .bind(score_row.submitted_at)
+            .execute(txn.as_mut())
+            .await
+        {
+            Ok(_) => info!("inserted new score"),
+            Err(SqlxError::Database(db_err)) if db_err.is_unique_violation() => {
+                info!("score was a duplicate");
+                return Err(ScoreInsertionError::Duplicate);
+            }
+            Err(error) => {
+                error!(%error, "failed to insert score");
+                return Err(ScoreInsertionError::UnexpectedSqlx {
+                    target: InsertionTarget::Score,
+                    error,
+                });
+            }
+        }
+
+        txn.commit().await.map_err(ScoreInsertionError::CommitTxn)?;
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, UserRow>("SELECT user_id FROM users")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(users) => {
+                for user in users {
+                    debug!(?user, "user");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get users");
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, GuildUserRow>("SELECT guild_id, user_id FROM guild_users")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(guild_users) => {
+                for guild_user in guild_users {
+                    debug!(?guild_user, "guild user");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get guild users");
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, ScoreRow>("SELECT * FROM travle_scores")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(scores) => {
+                for score in scores {
+                    debug!(?score, "score");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get scores");
+            }
+        }
+
+        Ok(InsertedScore {
+            best_so_far,
+            on_time: score_row.on_time(),
+            board: score_row.board as usize,
+        })
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct ScoreRow {
+    pub guild_id: i64,
+    pub user_id: i64,
+    pub guesses: i32,
+    pub extra_guesses: Option<i32>,
+    pub board: i32,
+    pub day_added: i32,
+    pub message_id: Option<i64>,
+    pub channel_id: Option<i64>,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+impl ScoreRow {
+    /// Whether this row records `score`, whenever and wherever it was submitted.
+    pub fn records(&self, score: &Score) -> bool {
+        (self.guesses, self.extra_guesses, self.board)
+            == (
+                score.guesses as i32,
+                score
+                    .extra_guesses
+                    .map(|extra_guesses| extra_guesses as i32),
+                score.board as i32,
+            )
+    }
+
+    pub fn from_score_at_opt(
+        score: Score,
+        guild_id: GuildId,
+        user_id: UserId,
+        submission: Submission,
+    ) -> Option<Self> {
+        let Score {
+            guesses,
+            extra_guesses,
+            board,
+        } = score;
+
+        Some(ScoreRow {
+            guild_id: guild_id.get() as i64,
+            user_id: user_id.get() as i64,
+            guesses: guesses as i32,
+            extra_guesses: extra_guesses.map(|extra| extra as i32),
+            board: board as i32,
+            day_added: utils::board_on_date(utils::date_from_utc(submission.submitted_at))? as i32,
+            message_id: submission
+                .message_id
+                .map(|message_id| message_id.get() as i64),
+            channel_id: Some(submission.channel_id.get() as i64),
+            submitted_at: Some(submission.submitted_at),
+        })
+    }
+
+    pub fn on_time(&self) -> bool {
+        self.day_added == self.board
+    }
+}
+
+pub struct InsertedScore {
+    pub best_so_far: bool,
+    pub on_time: bool,
+    pub board: usize,
+}
+
+impl crate::game::InsertedScore for InsertedScore {
+    fn is_best_so_far(&self) -> bool {
+        self.best_so_far
+    }
+
+    fn is_on_time(&self) -> bool {
+        self.on_time
+    }
+
+    fn board(&self) -> usize {
+        self.board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::{ParseScoreError, Score};
+
+    #[test]
+    fn parse_perfect() {
+        let raw = indoc! {"
+            #travle #615 +0 (Perfect)
+            ✅✅✅✅
+            https://travle.earth
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 615);
+        assert_eq!(score.guesses, 4);
+        assert_eq!(score.extra_guesses, Some(0));
+    }
+
+    #[test]
+    fn parse_extra_guesses() {
+        let raw = indoc! {"
+            #travle #616 +2
+            🟧✅🟥✅✅
+            https://travle.earth
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 616);
+        assert_eq!(score.guesses, 5);
+        assert_eq!(score.extra_guesses, Some(2));
+    }
+
+    #[test]
+    fn parse_failed() {
+        let raw = indoc! {"
+            #travle #617 (3 away)
+            🟧🟥🟥⬛🟧🟥🟧🟥
+            https://travle.earth
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 617);
+        assert_eq!(score.guesses, 8);
+        assert_eq!(score.extra_guesses, None);
+    }
+
+    #[test]
+    fn reject_too_many_extra_guesses() {
+        let raw = indoc! {"
+            #travle #616 +3
+            🟧✅🟥
+        "};
+
+        assert!(matches!(
+            raw.parse::<Score>(),
+            Err(ParseScoreError::Inconsistent)
+        ));
+    }
+}
@@ -0,0 +1,24 @@
+use sqlx::FromRow;
+
+use crate::game::{leaderboards::Tabled, travle::Travle};
+
+#[derive(Clone, Debug, FromRow)]
+pub struct Entry {
+    extra_guesses: i32,
+}
+
+impl Tabled for Travle {
+    type Entry = Entry;
+
+    fn describe(entry: &Entry) -> String {
+        format!(
+            "(+{}{})",
+            entry.extra_guesses,
+            if entry.extra_guesses == 0 {
+                ", perfect"
+            } else {
+                ""
+            },
+        )
+    }
+}
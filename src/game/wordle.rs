@@ -0,0 +1,727 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use indoc::indoc;
+use serenity::{
+    all::UserId,
+    model::prelude::{GuildId, User},
+};
+use sqlx::{postgres::PgRow, Error as SqlxError, FromRow, PgPool, Row as _};
+use thiserror::Error;
+use tracing::{debug, error, info};
+
+use super::{
+    CalculateAllTimeError, CalculateDailyError, ScoreInsertionError, ScoreTable, Submission,
+};
+use crate::{
+    game::leaderboards::{AllTime, Daily},
+    persist::{insert_guild_user, GuildUserRow, InsertionTarget, UserRow},
+};
+
+pub mod leaderboards;
+pub mod utils;
+
+macro_rules! failed {
+    () => {
+        7
+    };
+}
+
+/// The number of guesses recorded for a board that wasn't solved ("X/6").
+pub const FAILED: usize = failed!();
+
+pub struct Wordle;
+
+impl super::Game for Wordle {
+    type Score = Score;
+    type DailyLeaderboard = Daily;
+    type AllTimeLeaderboard = AllTime;
+
+    fn slug() -> &'static str {
+        "wordle"
+    }
+
+    fn display_name() -> &'static str {
+        "Wordle"
+    }
+
+    const SCORE_TABLE: ScoreTable = ScoreTable {
+        name: "wordle_scores",
+        board: "s.board",
+        on_time: "s.board = s.day_added",
+        score: "s.guesses",
+        ranked: concat!("s.guesses != ", failed!()),
+        higher_is_better: false,
+        time_tiebreak: false,
+    };
+
+    fn board_now() -> usize {
+        utils::board_now()
+    }
+
+    fn today() -> NaiveDate {
+        utils::today()
+    }
+
+    fn board_on_date(date: NaiveDate) -> Option<usize> {
+        utils::board_on_date(date)
+    }
+
+    async fn daily_leaderboard(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        board: usize,
+    ) -> Result<Daily, CalculateDailyError> {
+        Daily::calculate::<Self>(db_pool, guild_id, board).await
+    }
+
+    async fn all_time_leaderboard(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        include_today: bool,
+        include_late: bool,
+    ) -> Result<AllTime, CalculateAllTimeError> {
+        AllTime::calculate::<Self>(db_pool, guild_id, include_today, include_late).await
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Score {
+    /// The number of guesses taken, or [`FAILED`] if the board wasn't solved.
+    pub guesses: usize,
+    pub hard_mode: bool,
+    pub board: usize,
+}
+
+impl Score {
+    pub fn solved(&self) -> bool {
+        self.guesses != FAILED
+    }
+}
+
+impl FromStr for Score {
+    type Err = ParseScoreError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let mut lines = raw.trim().lines();
+
+        let details = lines
+            .next()
+            .ok_or(ParseScoreError::Empty)?
+            .strip_prefix("Wordle ")
+            .ok_or(ParseScoreError::Missing(Section::Details))?;
+
+        let (board_str, guesses_str) = details
+            .trim()
+            .split_once(' ')
+            .ok_or(ParseScoreError::Missing(Section::Guesses))?;
+
+        let board = board_str
+            .replace(',', "")
+            .parse::<usize>()
+            .map_err(|_| ParseScoreError::NotANumber(Number::Board))?;
+
+        let guesses_str = guesses_str.trim();
+        let (guesses_str, hard_mode) = match guesses_str.strip_suffix('*') {
+            Some(stripped) => (stripped, true),
+            None => (guesses_str, false),
+        };
+
+        let (guess_str, total) = guesses_str
+            .split_once('/')
+            .ok_or(ParseScoreError::InvalidFormat(Section::Guesses))?;
+
+        if total != "6" {
+            return Err(ParseScoreError::InvalidFormat(Section::Guesses));
+        }
+
+        let guesses = guess_str
+            .parse::<usize>()
+            .map_err(|_| ParseScoreError::NotANumber(Number::Guess))
+            .or_else(|err| {
+                if guess_str == "X" {
+                    Ok(FAILED)
+                } else {
+                    Err(err)
+                }
+            })?;
+
+        if !(1..=FAILED).contains(&guesses) {
+            return Err(ParseScoreError::InvalidFormat(Section::Guesses));
+        }
+
+        let rows = lines
+            .map(str::trim)
+            .skip_while(|line| line.is_empty())
+            .take_while(|line| !line.is_empty())
+            .map(|line| {
+                let row = line
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(|c| match c {
+                        '🟩' | '🟧' => Some(true),
+                        '🟨' | '🟦' | '⬛' | '⬜' => Some(false),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(ParseScoreError::InvalidFormat(Section::Grid))?;
+
+                if row.len() != 5 {
+                    return Err(ParseScoreError::InvalidFormat(Section::Grid));
+                }
+
+                Ok(row.into_iter().all(|correct| correct))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if rows.is_empty() {
+            return Err(ParseScoreError::Missing(Section::Grid));
+        }
+
+        // Every row is one guess, so a failed board has all six rows and none of them are solved,
+        // while a solved board has exactly one solved row, and it's the last one. Anything else
+        // means the score has been tampered with.
+        let solved_at = rows.iter().position(|&solved| solved);
+        let consistent = match solved_at {
+            Some(index) => index + 1 == rows.len() && rows.len() == guesses,
+            None => rows.len() == 6 && guesses == FAILED,
+        };
+
+        if !consistent {
+            return Err(ParseScoreError::Inconsistent);
+        }
+
+        Ok(Score {
+            guesses,
+            hard_mode,
+            board,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum ParseScoreError {
+    #[error("string is empty")]
+    Empty,
+
+    #[error("string does not contain a {0}")]
+    Missing(Section),
+
+    #[error("{0} was not formatted as expected")]
+    InvalidFormat(Section),
+
+    #[error("{0} is not a number")]
+    NotANumber(Number),
+
+    #[error("guess number and grid don't match")]
+    Inconsistent,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Section {
+    Details,
+    Guesses,
+    Grid,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Section::Details => write!(f, "details line"),
+            Section::Guesses => write!(f, "guesses"),
+            Section::Grid => write!(f, "grid section"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Number {
+    Board,
+    Guess,
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Board => write!(f, "board number"),
+            Number::Guess => write!(f, "guess"),
+        }
+    }
+}
+
+impl super::Score for Score {
+    type Game = Wordle;
+
+    fn is_recorded_in(&self, row: &PgRow) -> Result<bool, SqlxError> {
+        Ok(ScoreRow::from_row(row)?.records(self))
+    }
+
+    async fn insert(
+        self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        user: &User,
+        submission: Submission,
+    ) -> Result<impl super::InsertedScore, ScoreInsertionError> {
+        let mut txn = db_pool
+            .begin()
+            .await
+            .map_err(ScoreInsertionError::BeginTxn)?;
+
+        insert_guild_user(&mut txn, guild_id, user).await?;
+
+        let score_row = ScoreRow::from_score_at_opt(self, guild_id, user.id, submission)
+            .ok_or(ScoreInsertionError::BeforeFirstBoard)?;
+
+        let get_best_score = sqlx::query(indoc! {"
+            SELECT guesses FROM wordle_scores
+            WHERE
+                guild_id = $1
+                AND user_id != $2
+                AND board = $3
+                AND board = day_added
+                AND guesses != $4
+            ORDER BY guesses ASC
+            LIMIT 1;
+        "});
+        let best_so_far = match get_best_score
+            .bind(guild_id.get() as i64)
+            .bind(user.id.get() as i64)
+            .bind(score_row.board)
+            .bind(FAILED as i32)
+            .fetch_one(txn.as_mut())
+            .await
+            .and_then(|row| row.try_get::<i32, _>(0))
+        {
+            Ok(best_guesses) => {
+                info!(
+                    %best_guesses,
+                    board = %score_row.board,
+                    "got best existing score for this board"
+                );
+
+                score_row.guesses <= best_guesses
+            }
+            Err(SqlxError::RowNotFound) => {
+                info!(
+                    board = %score_row.board,
+                    "there are no on-time scores for this board"
+                );
+                score_row.guesses != FAILED as i32
+            }
+            Err(error) => {
+                error!(
+                    %error,
+                    board = %score_row.board,
+                    "failed to get current best score for this board"
+                );
+                true
+            }
+        };
+
+        let insert_score = sqlx::query(indoc! {"
+            INSERT INTO wordle_scores (
+                guild_id,
+                user_id,
+                guesses,
+                hard_mode,
+                board,
+                day_added,
+                message_id,
+                channel_id,
+                submitted_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9);
+        "});
+        match insert_score
+            .bind(score_row.guild_id)
+            .bind(score_row.user_id)
+            .bind(score_row.guesses)
+            .bind(score_row.hard_mode)
+            .bind(score_row.board)
+            .bind(score_row.day_added)
+            .bind(score_row.message_id)
+            .bind(score_row.channel_id)
+            .bind(score_row.submitted_at)
+            .execute(txn.as_mut())
+            .await
+        {
+            Ok(_) => info!("inserted new score"),
+            Err(SqlxError::Database(db_err)) if db_err.is_unique_violation() => {
+                info!("score was a duplicate");
+                return Err(ScoreInsertionError::Duplicate);
+            }
+            Err(error) => {
+                error!(%error, "failed to insert score");
+                return Err(ScoreInsertionError::UnexpectedSqlx {
+                    target: InsertionTarget::Score,
+                    error,
+                });
+            }
+        }
+
+        txn.commit().await.map_err(ScoreInsertionError::CommitTxn)?;
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, UserRow>("SELECT user_id FROM users")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(users) => {
+                for user in users {
+                    debug!(?user, "user");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get users");
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, GuildUserRow>("SELECT guild_id, user_id FROM guild_users")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(guild_users) => {
+                for guild_user in guild_users {
+                    debug!(?guild_user, "guild user");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get guild users");
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, ScoreRow>("SELECT * FROM wordle_scores")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(scores) => {
+                for score in scores {
+                    debug!(?score, "score");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get scores");
+            }
+        }
+
+        Ok(InsertedScore {
+            best_so_far,
+            on_time: score_row.on_time(),
+            board: score_row.board as usize,
+        })
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct ScoreRow {
+    pub guild_id: i64,
+    pub user_id: i64,
+    pub guesses: i32,
+    pub hard_mode: bool,
+    pub board: i32,
+    pub day_added: i32,
+    pub message_id: Option<i64>,
+    pub channel_id: Option<i64>,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+impl ScoreRow {
+    /// Whether this row records `score`, whenever and wherever it was submitted.
+    pub fn records(&self, score: &Score) -> bool {
+        (self.guesses, self.hard_mode, self.board)
+            == (score.guesses as i32, score.hard_mode, score.board as i32)
+    }
+
+    pub fn from_score_at_opt(
+        score: Score,
+        guild_id: GuildId,
+        user_id: UserId,
+        submission: Submission,
+    ) -> Option<Self> {
+        let Score {
+            guesses,
+            hard_mode,
+            board,
+        } = score;
+
+        Some(ScoreRow {
+            guild_id: guild_id.get() as i64,
+            user_id: user_id.get() as i64,
+            guesses: guesses as i32,
+            hard_mode,
+            board: board as i32,
+            day_added: utils::board_on_date(utils::date_from_utc(submission.submitted_at))? as i32,
+            message_id: submission
+                .message_id
+                .map(|message_id| message_id.get() as i64),
+            channel_id: Some(submission.channel_id.get() as i64),
+            submitted_at: Some(submission.submitted_at),
+        })
+    }
+
+    pub fn on_time(&self) -> bool {
+        self.day_added == self.board
+    }
+}
+
+pub struct InsertedScore {
+    pub best_so_far: bool,
+    pub on_time: bool,
+    pub board: usize,
+}
+
+impl crate::game::InsertedScore for InsertedScore {
+    fn is_best_so_far(&self) -> bool {
+        self.best_so_far
+    }
+
+    fn is_on_time(&self) -> bool {
+        self.on_time
+    }
+
+    fn board(&self) -> usize {
+        self.board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Days, NaiveTime};
+    use indoc::indoc;
+    use serenity::all::{ChannelId, GuildId, Message, MessageId, UserId};
+
+    use super::{utils, ParseScoreError, Score, ScoreRow, Wordle, FAILED};
+    use crate::game::{Game, Submission};
+
+    fn submitted_on_day(board: usize, days_later: u64) -> Submission {
+        let date = utils::date_of_board(board) + Days::new(days_later);
+
+        Submission {
+            message_id: Some(MessageId::new(1)),
+            channel_id: ChannelId::new(1),
+            submitted_at: date.and_time(NaiveTime::MIN).and_utc() + chrono::Duration::hours(12),
+        }
+    }
+
+    #[test]
+    fn failed_boards_are_not_ranked() {
+        let raw = indoc! {"
+            Wordle 1,235 X/6
+
+            ⬛⬛⬛🟨⬛
+            ⬛🟩⬛⬛⬛
+            ⬛🟩🟩⬛⬛
+            ⬛🟩🟩🟩⬛
+            🟩🟩🟩🟩⬛
+            🟩🟩🟩🟩⬛
+        "};
+        let score = raw.parse::<Score>().unwrap();
+
+        // Places and medals only go to scores that pass the ranked clause
+        let excluded = Wordle::SCORE_TABLE
+            .ranked
+            .strip_prefix("s.guesses != ")
+            .and_then(|guesses| guesses.parse::<usize>().ok());
+        assert_eq!(excluded, Some(score.guesses));
+    }
+
+    #[test]
+    fn on_time_is_decided_by_message_timestamp() {
+        let score = Score {
+            board: 1234,
+            guesses: 4,
+            hard_mode: false,
+        };
+        let (guild_id, user_id) = (GuildId::new(1), UserId::new(1));
+
+        let on_time = ScoreRow::from_score_at_opt(
+            score.clone(),
+            guild_id,
+            user_id,
+            submitted_on_day(1234, 0),
+        )
+        .unwrap();
+        let late = ScoreRow::from_score_at_opt(score, guild_id, user_id, submitted_on_day(1234, 1))
+            .unwrap();
+
+        assert!(on_time.on_time());
+        assert!(!late.on_time());
+        assert_eq!(late.message_id, Some(1));
+    }
+
+    #[test]
+    fn edits_after_the_board_closes_are_late() {
+        let score = Score {
+            board: 1234,
+            guesses: 2,
+            hard_mode: false,
+        };
+        let mut msg = Message::default();
+        msg.timestamp = submitted_on_day(1234, 0).submitted_at.into();
+        msg.edited_timestamp = Some(submitted_on_day(1234, 1).submitted_at.into());
+
+        let edited = ScoreRow::from_score_at_opt(
+            score,
+            GuildId::new(1),
+            UserId::new(1),
+            Submission::edited(&msg),
+        )
+        .unwrap();
+
+        assert!(!edited.on_time());
+    }
+
+    #[test]
+    fn rows_record_only_the_same_score() {
+        let score = Score {
+            board: 1234,
+            guesses: 3,
+            hard_mode: true,
+        };
+        let row = ScoreRow::from_score_at_opt(
+            score.clone(),
+            GuildId::new(1),
+            UserId::new(1),
+            submitted_on_day(1234, 0),
+        )
+        .unwrap();
+
+        assert!(row.records(&score));
+        assert!(!row.records(&Score {
+            hard_mode: false,
+            ..score.clone()
+        }));
+        assert!(!row.records(&Score {
+            guesses: 4,
+            ..score
+        }));
+    }
+
+    #[test]
+    fn parse_solved() {
+        let raw = indoc! {"
+            Wordle 1,234 4/6
+
+            ⬛🟨⬛⬛⬛
+            ⬛🟩🟨⬛⬛
+            🟩🟩⬛🟩⬛
+            🟩🟩🟩🟩🟩
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 1234);
+        assert_eq!(score.guesses, 4);
+        assert!(!score.hard_mode);
+        assert!(score.solved());
+    }
+
+    #[test]
+    fn parse_hard_mode_light_theme() {
+        let raw = indoc! {"
+            Wordle 987 2/6*
+
+            ⬜🟨⬜⬜🟩
+            🟩🟩🟩🟩🟩
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 987);
+        assert_eq!(score.guesses, 2);
+        assert!(score.hard_mode);
+    }
+
+    #[test]
+    fn parse_high_contrast() {
+        let raw = indoc! {"
+            Wordle 1,001 3/6
+
+            ⬛🟦⬛⬛⬛
+            🟧⬛🟧🟦⬛
+            🟧🟧🟧🟧🟧
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 1001);
+        assert_eq!(score.guesses, 3);
+    }
+
+    #[test]
+    fn parse_failed() {
+        let raw = indoc! {"
+            Wordle 1,235 X/6*
+
+            ⬛⬛⬛🟨⬛
+            ⬛🟩⬛⬛⬛
+            ⬛🟩🟩⬛⬛
+            ⬛🟩🟩🟩⬛
+            🟩🟩🟩🟩⬛
+            🟩🟩🟩🟩⬛
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 1235);
+        assert_eq!(score.guesses, FAILED);
+        assert!(score.hard_mode);
+        assert!(!score.solved());
+    }
+
+    #[test]
+    fn reject_row_count_mismatch() {
+        let raw = indoc! {"
+            Wordle 1,234 2/6
+
+            ⬛🟨⬛⬛⬛
+            ⬛🟩🟨⬛⬛
+            🟩🟩🟩🟩🟩
+        "};
+
+        assert!(matches!(
+            raw.parse::<Score>(),
+            Err(ParseScoreError::Inconsistent)
+        ));
+    }
+
+    #[test]
+    fn reject_failed_with_solved_row() {
+        let raw = indoc! {"
+            Wordle 1,234 X/6
+
+            ⬛🟨⬛⬛⬛
+            ⬛🟩🟨⬛⬛
+            ⬛🟩🟨⬛⬛
+            ⬛🟩🟨⬛⬛
+            ⬛🟩🟨⬛⬛
+            🟩🟩🟩🟩🟩
+        "};
+
+        assert!(matches!(
+            raw.parse::<Score>(),
+            Err(ParseScoreError::Inconsistent)
+        ));
+    }
+
+    #[test]
+    fn reject_missing_grid() {
+        let raw = "Wordle 1,234 4/6";
+
+        assert!(matches!(
+            raw.parse::<Score>(),
+            Err(ParseScoreError::Missing(_))
+        ));
+    }
+}
@@ -0,0 +1,21 @@
+use sqlx::FromRow;
+
+use crate::game::{leaderboards::Tabled, wordle::Wordle};
+
+#[derive(Clone, Debug, FromRow)]
+pub struct Entry {
+    guesses: i32,
+    hard_mode: bool,
+}
+
+impl Tabled for Wordle {
+    type Entry = Entry;
+
+    fn describe(entry: &Entry) -> String {
+        format!(
+            "({}/6{})",
+            entry.guesses,
+            if entry.hard_mode { "\\*" } else { "" },
+        )
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use tap::TryConv;
+
+/// Get the date on which a board with a given number occurred.
+pub fn date_of_board(number: usize) -> NaiveDate {
+    start_date() + Days::new(number as u64)
+}
+
+/// Get the date on which board 0 occurred. Unlike most games, Wordle numbers its boards from 0.
+fn start_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2021, 6, 19).expect("19 June 2021 is a valid date")
+}
+
+/// Get today's date, in UTC since Wordle boards roll over at each player's local midnight.
+pub fn today() -> NaiveDate {
+    date_from_utc(Utc::now())
+}
+
+pub fn date_from_utc(date: DateTime<Utc>) -> NaiveDate {
+    // Wordle uses the player's local date, which we don't know, so UTC is the best we can do
+    date.date_naive()
+}
+
+/// Get the number of the board that occurred on `date`. Returns `None` if `date` was before day 0.
+pub fn board_on_date(date: NaiveDate) -> Option<usize> {
+    let days_since = (date - start_date()).num_days();
+    days_since.try_conv::<usize>().ok()
+}
+
+/// Get the number of the board that is active right now.
+pub fn board_now() -> usize {
+    board_on_date(today()).expect("today is always after day 0")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    #[test]
+    fn board_0_is_19_jun_2021() {
+        let manual_date =
+            NaiveDate::from_ymd_opt(2021, 6, 19).expect("19 June 2021 is a valid date");
+        let calc_date = super::date_of_board(0);
+
+        assert_eq!(manual_date, calc_date);
+    }
+
+    #[test]
+    fn board_on_18_jun_2021_is_none() {
+        let manual_date =
+            NaiveDate::from_ymd_opt(2021, 6, 18).expect("18 June 2021 is a valid date");
+        let board_num = super::board_on_date(manual_date);
+
+        assert_eq!(board_num, None);
+    }
+
+    #[test]
+    fn board_1000_is_15_mar_2024() {
+        let manual_date =
+            NaiveDate::from_ymd_opt(2024, 3, 15).expect("15 March 2024 is a valid date");
+        let calc_date = super::date_of_board(1000);
+
+        assert_eq!(manual_date, calc_date);
+    }
+
+    #[test]
+    fn board_on_15_mar_2024_is_1000() {
+        let manual_date =
+            NaiveDate::from_ymd_opt(2024, 3, 15).expect("15 March 2024 is a valid date");
+        let board_num = super::board_on_date(manual_date);
+
+        assert_eq!(board_num, Some(1000));
+    }
+}
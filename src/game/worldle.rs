@@ -0,0 +1,595 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use indoc::indoc;
+use serenity::{
+    all::UserId,
+    model::prelude::{GuildId, User},
+};
+use sqlx::{postgres::PgRow, Error as SqlxError, FromRow, PgPool, Row as _};
+use thiserror::Error;
+use tracing::{debug, error, info};
+
+use super::{
+    CalculateAllTimeError, CalculateDailyError, ScoreInsertionError, ScoreTable, Submission,
+};
+use crate::{
+    game::leaderboards::{AllTime, Daily},
+    persist::{insert_guild_user, GuildUserRow, InsertionTarget, UserRow},
+};
+
+pub mod leaderboards;
+pub mod utils;
+
+macro_rules! failed {
+    () => {
+        7
+    };
+}
+
+/// The number of guesses recorded for a board that wasn't solved ("X/6").
+pub const FAILED: usize = failed!();
+
+pub struct Worldle;
+
+impl super::Game for Worldle {
+    type Score = Score;
+    type DailyLeaderboard = Daily;
+    type AllTimeLeaderboard = AllTime;
+
+    fn slug() -> &'static str {
+        "worldle"
+    }
+
+    fn display_name() -> &'static str {
+        "Worldle"
+    }
+
+    const SCORE_TABLE: ScoreTable = ScoreTable {
+        name: "worldle_scores",
+        board: "s.board",
+        on_time: "s.board = s.day_added",
+        score: "s.guesses",
+        ranked: concat!("s.guesses != ", failed!()),
+        higher_is_better: false,
+        time_tiebreak: false,
+    };
+
+    fn board_now() -> usize {
+        utils::board_now()
+    }
+
+    fn today() -> NaiveDate {
+        utils::today()
+    }
+
+    fn board_on_date(date: NaiveDate) -> Option<usize> {
+        utils::board_on_date(date)
+    }
+
+    async fn daily_leaderboard(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        board: usize,
+    ) -> Result<Daily, CalculateDailyError> {
+        Daily::calculate::<Self>(db_pool, guild_id, board).await
+    }
+
+    async fn all_time_leaderboard(
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        include_today: bool,
+        include_late: bool,
+    ) -> Result<AllTime, CalculateAllTimeError> {
+        AllTime::calculate::<Self>(db_pool, guild_id, include_today, include_late).await
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Score {
+    /// The number of guesses taken, or [`FAILED`] if the board wasn't solved.
+    pub guesses: usize,
+    pub board: usize,
+}
+
+impl Score {
+    pub fn solved(&self) -> bool {
+        self.guesses != FAILED
+    }
+}
+
+impl FromStr for Score {
+    type Err = ParseScoreError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let mut lines = raw.trim().lines();
+
+        let details = lines
+            .next()
+            .ok_or(ParseScoreError::Empty)?
+            .strip_prefix("#Worldle #")
+            .ok_or(ParseScoreError::Missing(Section::Details))?;
+
+        let (board_str, date_guesses) = details
+            .split_once(' ')
+            .ok_or(ParseScoreError::Missing(Section::BoardNumber))?;
+
+        let board = board_str
+            .parse::<usize>()
+            .map_err(|_| ParseScoreError::NotANumber(Number::Board))?;
+
+        // Newer share strings include the date in brackets before the guesses, older ones don't
+        let guesses = match date_guesses.strip_prefix('(') {
+            Some(date_guesses) => {
+                date_guesses
+                    .split_once(") ")
+                    .ok_or(ParseScoreError::Missing(Section::Guesses))?
+                    .1
+            }
+            None => date_guesses,
+        };
+
+        let (guess_str, total) = guesses
+            .split_whitespace()
+            .next()
+            .ok_or(ParseScoreError::Missing(Section::Guesses))?
+            .split_once('/')
+            .ok_or(ParseScoreError::InvalidFormat(Section::Guesses))?;
+
+        if total != "6" {
+            return Err(ParseScoreError::InvalidFormat(Section::Guesses));
+        }
+
+        let guesses = guess_str
+            .parse::<usize>()
+            .map_err(|_| ParseScoreError::NotANumber(Number::Guess))
+            .or_else(|err| {
+                if guess_str == "X" {
+                    Ok(FAILED)
+                } else {
+                    Err(err)
+                }
+            })?;
+
+        if !(1..=FAILED).contains(&guesses) {
+            return Err(ParseScoreError::InvalidFormat(Section::Guesses));
+        }
+
+        // Each row is five proximity squares followed by a direction arrow (or 🎉 when correct).
+        // Bonus round results can follow the grid, so stop at the first line that isn't a row.
+        let rows = lines
+            .map(str::trim)
+            .take_while(|line| line.starts_with(['🟩', '🟨', '⬜', '⬛']))
+            .map(|line| {
+                let squares = line
+                    .chars()
+                    .take_while(|c| matches!(c, '🟩' | '🟨' | '⬜' | '⬛'))
+                    .collect::<Vec<_>>();
+
+                if squares.len() != 5 {
+                    return Err(ParseScoreError::InvalidFormat(Section::Grid));
+                }
+
+                Ok(squares.into_iter().all(|c| c == '🟩'))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if rows.is_empty() {
+            return Err(ParseScoreError::Missing(Section::Grid));
+        }
+
+        // A guess is only 100% proximity when it's correct, so a solved board has exactly one full
+        // row, and it's the last one, while a failed board has six rows and no full ones.
+        let solved_at = rows.iter().position(|&solved| solved);
+        let consistent = match solved_at {
+            Some(index) => index + 1 == rows.len() && rows.len() == guesses,
+            None => rows.len() == 6 && guesses == FAILED,
+        };
+
+        if !consistent {
+            return Err(ParseScoreError::Inconsistent);
+        }
+
+        Ok(Score { guesses, board })
+    }
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum ParseScoreError {
+    #[error("string is empty")]
+    Empty,
+
+    #[error("string does not contain a {0}")]
+    Missing(Section),
+
+    #[error("{0} was not formatted as expected")]
+    InvalidFormat(Section),
+
+    #[error("{0} is not a number")]
+    NotANumber(Number),
+
+    #[error("guess number and grid don't match")]
+    Inconsistent,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Section {
+    Details,
+    BoardNumber,
+    Guesses,
+    Grid,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Section::Details => write!(f, "details line"),
+            Section::BoardNumber => write!(f, "board number"),
+            Section::Guesses => write!(f, "guesses"),
+            Section::Grid => write!(f, "grid section"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Number {
+    Board,
+    Guess,
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Board => write!(f, "board number"),
+            Number::Guess => write!(f, "guess"),
+        }
+    }
+}
+
+impl super::Score for Score {
+    type Game = Worldle;
+
+    fn is_recorded_in(&self, row: &PgRow) -> Result<bool, SqlxError> {
+        Ok(ScoreRow::from_row(row)?.records(self))
+    }
+
+    async fn insert(
+        self,
+        db_pool: &PgPool,
+        guild_id: GuildId,
+        user: &User,
+        submission: Submission,
+    ) -> Result<impl super::InsertedScore, ScoreInsertionError> {
+        let mut txn = db_pool
+            .begin()
+            .await
+            .map_err(ScoreInsertionError::BeginTxn)?;
+
+        insert_guild_user(&mut txn, guild_id, user).await?;
+
+        let score_row = ScoreRow::from_score_at_opt(self, guild_id, user.id, submission)
+            .ok_or(ScoreInsertionError::BeforeFirstBoard)?;
+
+        let get_best_score = sqlx::query(indoc! {"
+            SELECT guesses FROM worldle_scores
+            WHERE
+                guild_id = $1
+                AND user_id != $2
+                AND board = $3
+                AND board = day_added
+                AND guesses != $4
+            ORDER BY guesses ASC
+            LIMIT 1;
+        "});
+        let best_so_far = match get_best_score
+            .bind(guild_id.get() as i64)
+            .bind(user.id.get() as i64)
+            .bind(score_row.board)
+            .bind(FAILED as i32)
+            .fetch_one(txn.as_mut())
+            .await
+            .and_then(|row| row.try_get::<i32, _>(0))
+        {
+            Ok(best_guesses) => {
+                info!(
+                    %best_guesses,
+                    board = %score_row.board,
+                    "got best existing score for this board"
+                );
+
+                score_row.guesses <= best_guesses
+            }
+            Err(SqlxError::RowNotFound) => {
+                info!(
+                    board = %score_row.board,
+                    "there are no on-time scores for this board"
+                );
+                score_row.guesses != FAILED as i32
+            }
+            Err(error) => {
+                error!(
+                    %error,
+                    board = %score_row.board,
+                    "failed to get current best score for this board"
+                );
+                true
+            }
+        };
+
+        let insert_score = sqlx::query(indoc! {"
+            INSERT INTO worldle_scores (
+                guild_id,
+                user_id,
+                guesses,
+                board,
+                day_added,
+                message_id,
+                channel_id,
+                submitted_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8);
+        "});
+        match insert_score
+            .bind(score_row.guild_id)
+            .bind(score_row.user_id)
+            .bind(score_row.guesses)
+            .bind(score_row.board)
+            .bind(score_row.day_added)
+            .bind(score_row.message_id)
+            .bind(score_row.channel_id)
+            .bind(score_row.submitted_at)
+            .execute(txn.as_mut())
+            .await
+        {
+            Ok(_) => info!("inserted new score"),
+            Err(SqlxError::Database(db_err)) if db_err.is_unique_violation() => {
+                info!("score was a duplicate");
+                return Err(ScoreInsertionError::Duplicate);
+            }
+            Err(error) => {
+                error!(%error, "failed to insert score");
+                return Err(ScoreInsertionError::UnexpectedSqlx {
+                    target: InsertionTarget::Score,
+                    error,
+                });
+            }
+        }
+
+        txn.commit().await.map_err(ScoreInsertionError::CommitTxn)?;
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, UserRow>("SELECT user_id FROM users")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(users) => {
+                for user in users {
+                    debug!(?user, "user");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get users");
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, GuildUserRow>("SELECT guild_id, user_id FROM guild_users")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(guild_users) => {
+                for guild_user in guild_users {
+                    debug!(?guild_user, "guild user");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get guild users");
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        match sqlx::query_as::<_, ScoreRow>("SELECT * FROM worldle_scores")
+            .fetch_all(db_pool)
+            .await
+        {
+            Ok(scores) => {
+                for score in scores {
+                    debug!(?score, "score");
+                }
+            }
+            Err(error) => {
+                error!(%error, "failed to get scores");
+            }
+        }
+
+        Ok(InsertedScore {
+            best_so_far,
+            on_time: score_row.on_time(),
+            board: score_row.board as usize,
+        })
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct ScoreRow {
+    pub guild_id: i64,
+    pub user_id: i64,
+    pub guesses: i32,
+    pub board: i32,
+    pub day_added: i32,
+    pub message_id: Option<i64>,
+    pub channel_id: Option<i64>,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+impl ScoreRow {
+    /// Whether this row records `score`, whenever and wherever it was submitted.
+    pub fn records(&self, score: &Score) -> bool {
+        (self.guesses, self.board) == (score.guesses as i32, score.board as i32)
+    }
+
+    pub fn from_score_at_opt(
+        score: Score,
+        guild_id: GuildId,
+        user_id: UserId,
+        submission: Submission,
+    ) -> Option<Self> {
+        let Score { guesses, board } = score;
+
+        Some(ScoreRow {
+            guild_id: guild_id.get() as i64,
+            user_id: user_id.get() as i64,
+            guesses: guesses as i32,
+            board: board as i32,
+            day_added: utils::board_on_date(utils::date_from_utc(submission.submitted_at))? as i32,
+            message_id: submission
+                .message_id
+                .map(|message_id| message_id.get() as i64),
+            channel_id: Some(submission.channel_id.get() as i64),
+            submitted_at: Some(submission.submitted_at),
+        })
+    }
+
+    pub fn on_time(&self) -> bool {
+        self.day_added == self.board
+    }
+}
+
+pub struct InsertedScore {
+    pub best_so_far: bool,
+    pub on_time: bool,
+    pub board: usize,
+}
+
+impl crate::game::InsertedScore for InsertedScore {
+    fn is_best_so_far(&self) -> bool {
+        self.best_so_far
+    }
+
+    fn is_on_time(&self) -> bool {
+        self.on_time
+    }
+
+    fn board(&self) -> usize {
+        self.board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::{ParseScoreError, Score, Worldle, FAILED};
+    use crate::game::Game;
+
+    #[test]
+    fn parse_solved() {
+        let raw = indoc! {"
+            #Worldle #1000 (16.10.2024) 3/6 (100%)
+            🟩🟩🟩🟨⬜⬅️
+            🟩🟩🟩🟩🟨↗️
+            🟩🟩🟩🟩🟩🎉
+
+            https://worldle.teuteuf.fr
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 1000);
+        assert_eq!(score.guesses, 3);
+        assert!(score.solved());
+    }
+
+    #[test]
+    fn parse_with_bonus_rounds() {
+        let raw = indoc! {"
+            #Worldle #1001 (17.10.2024) 1/6 (100%)
+            🟩🟩🟩🟩🟩🎉
+            🧭⭐🚩⭐🔤⭐
+            https://worldle.teuteuf.fr
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 1001);
+        assert_eq!(score.guesses, 1);
+    }
+
+    #[test]
+    fn parse_old_format() {
+        let raw = indoc! {"
+            #Worldle #222 2/6 (100%)
+            🟩🟩🟨⬜⬜↘️
+            🟩🟩🟩🟩🟩🎉
+            https://worldle.teuteuf.fr
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.board, 222);
+        assert_eq!(score.guesses, 2);
+    }
+
+    #[test]
+    fn parse_failed() {
+        let raw = indoc! {"
+            #Worldle #1000 (16.10.2024) X/6 (94%)
+            🟩🟩⬜⬜⬜➡️
+            🟩🟩🟩⬜⬜↗️
+            🟩🟩🟩🟨⬜⬆️
+            🟩🟩🟩🟩⬜⬆️
+            🟩🟩🟩🟩⬜↖️
+            🟩🟩🟩🟩🟨⬅️
+
+            https://worldle.teuteuf.fr
+        "};
+
+        let score = raw
+            .parse::<Score>()
+            .expect("should have successfully parsed raw string to Score");
+
+        assert_eq!(score.guesses, FAILED);
+        assert!(!score.solved());
+    }
+
+    #[test]
+    fn failed_boards_are_not_ranked() {
+        let raw = indoc! {"
+            #Worldle #1000 (16.10.2024) X/6 (94%)
+            🟩🟩⬜⬜⬜➡️
+            🟩🟩🟩⬜⬜↗️
+            🟩🟩🟩🟨⬜⬆️
+            🟩🟩🟩🟩⬜⬆️
+            🟩🟩🟩🟩⬜↖️
+            🟩🟩🟩🟩🟨⬅️
+        "};
+        let score = raw.parse::<Score>().unwrap();
+
+        // Places and medals only go to scores that pass the ranked clause
+        let excluded = Worldle::SCORE_TABLE
+            .ranked
+            .strip_prefix("s.guesses != ")
+            .and_then(|guesses| guesses.parse::<usize>().ok());
+        assert_eq!(excluded, Some(score.guesses));
+    }
+
+    #[test]
+    fn reject_row_count_mismatch() {
+        let raw = indoc! {"
+            #Worldle #1000 (16.10.2024) 1/6 (100%)
+            🟩🟩🟩🟨⬜⬅️
+            🟩🟩🟩🟩🟩🎉
+        "};
+
+        assert!(matches!(
+            raw.parse::<Score>(),
+            Err(ParseScoreError::Inconsistent)
+        ));
+    }
+}
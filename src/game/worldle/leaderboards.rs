@@ -0,0 +1,16 @@
+use sqlx::FromRow;
+
+use crate::game::{leaderboards::Tabled, worldle::Worldle};
+
+#[derive(Clone, Debug, FromRow)]
+pub struct Entry {
+    guesses: i32,
+}
+
+impl Tabled for Worldle {
+    type Entry = Entry;
+
+    fn describe(entry: &Entry) -> String {
+        format!("({}/6)", entry.guesses)
+    }
+}
@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use serenity::all::{ChannelId, GetMessages, Http, Message, MessageId};
+
+/// The start of 2015, which Discord snowflakes count their timestamps from.
+const DISCORD_EPOCH_MILLIS: i64 = 1_420_070_400_000;
+
+/// The maximum number of messages Discord will return in one request.
+const PAGE_SIZE: u8 = 100;
+
+/// Get the lowest possible ID for a message sent at or after `time`, for paging through history
+/// from a point in time.
+pub fn message_id_at(time: DateTime<Utc>) -> MessageId {
+    let millis = (time.timestamp_millis() - DISCORD_EPOCH_MILLIS).max(1) as u64;
+    MessageId::new(millis << 22)
+}
+
+/// Pages through a channel's messages, oldest first, starting after a given message.
+pub struct History {
+    channel_id: ChannelId,
+    cursor: MessageId,
+    done: bool,
+}
+
+impl History {
+    pub fn after(channel_id: ChannelId, cursor: MessageId) -> Self {
+        Self {
+            channel_id,
+            cursor,
+            done: false,
+        }
+    }
+
+    /// Fetch the next page of messages, in the order they were sent. Returns an empty page once
+    /// there are no more messages.
+    pub async fn next_page(&mut self, http: &Http) -> serenity::Result<Vec<Message>> {
+        if self.done {
+            return Ok(Vec::new());
+        }
+
+        let mut messages = self
+            .channel_id
+            .messages(http, GetMessages::new().after(self.cursor).limit(PAGE_SIZE))
+            .await?;
+        messages.sort_unstable_by_key(|msg| msg.id);
+
+        match messages.last() {
+            Some(last) => self.cursor = last.id,
+            None => self.done = true,
+        }
+        if messages.len() < PAGE_SIZE as usize {
+            self.done = true;
+        }
+
+        Ok(messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::message_id_at;
+
+    #[test]
+    fn message_id_round_trips_through_timestamp() {
+        let time = DateTime::from_timestamp_millis(1_729_166_400_000).unwrap();
+        let message_id = message_id_at(time);
+
+        assert_eq!(*message_id.created_at(), time);
+    }
+
+    #[test]
+    fn message_id_before_discord_epoch_is_clamped() {
+        let time = DateTime::from_timestamp_millis(0).unwrap();
+
+        assert_eq!(message_id_at(time).get(), 1 << 22);
+    }
+}
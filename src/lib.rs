@@ -1,32 +1,68 @@
-#![allow(async_fn_in_trait)]
+use std::{collections::HashMap, sync::Arc};
 
+use chrono::{TimeDelta, Utc};
 use serenity::{
     all::{
-        Command, CommandInteraction, CommandOptionType, GuildId, Interaction, ResolvedOption,
-        ResolvedValue,
+        Channel, ChannelId, Command, CommandInteraction, GuildId, Http, Interaction, Mention,
+        MessageId, MessageUpdateEvent,
     },
     async_trait,
-    builder::{
-        CreateAllowedMentions, CreateCommand, CreateCommandOption, CreateInteractionResponse,
-        CreateInteractionResponseMessage,
-    },
+    builder::{CreateInteractionResponse, CreateInteractionResponseMessage},
     model::{channel::Message, gateway::Ready},
     prelude::*,
 };
 use sqlx::PgPool;
 use tap::Pipe;
+use tokio::sync::Notify;
 use tracing::{debug, error, info, instrument, warn};
 
-use crate::game::{
-    flagle::Flagle, foodguessr::FoodGuessr, geogrid::GeoGrid, Game, InsertedScore, Score,
-    ScoreInsertionError,
+use crate::{
+    game::{
+        rating, DynGame, Game, InsertedScore, Registered, Score, ScoreInsertionError, Submission,
+        GAMES,
+    },
+    history::History,
+    settings::GuildSettings,
 };
 
+pub mod commands;
 pub mod game;
+pub mod history;
 pub mod persist;
+pub mod reactions;
+pub mod schedule;
+pub mod settings;
+
+/// How far back to look for messages that were missed while the bot was offline. Longer outages
+/// can be recovered from with `/backfill`.
+const MAX_CATCH_UP: TimeDelta = TimeDelta::days(2);
 
 pub struct Bot {
     pub db_pool: PgPool,
+
+    /// Notified each time the bot has finished catching up on missed messages, so that the
+    /// results scheduler doesn't close boards that are still being caught up on.
+    pub caught_up: Arc<Notify>,
+}
+
+/// How a message was accepted as a score.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Accepted {
+    /// The score was recorded for this board.
+    Recorded(usize),
+
+    /// The score was already on record, so nothing changed.
+    Duplicate,
+}
+
+/// Why a message that is a score wasn't accepted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rejected {
+    /// The game is disabled, or the score couldn't be recorded.
+    Ignored,
+
+    /// Scores for the game aren't recorded in the message's channel, only in these ones.
+    WrongChannel(Vec<ChannelId>),
 }
 
 #[async_trait]
@@ -34,62 +70,28 @@ impl EventHandler for Bot {
     async fn ready(&self, ctx: Context, ready: Ready) {
         info!(username = %ready.user.name, "connected!");
 
-        match Command::create_global_command(
-            &ctx.http,
-            CreateCommand::new("leaderboard")
-                .description("View the leaderboard")
-                .add_option(
-                    CreateCommandOption::new(
-                        CommandOptionType::SubCommand,
-                        "today",
-                        "View the leaderboard for today",
-                    )
-                    .add_sub_option(
-                        CreateCommandOption::new(
-                            CommandOptionType::String,
-                            "game",
-                            "The game to view the leaderboard for",
-                        )
-                        .required(true)
-                        .add_string_choice("GeoGrid", "geogrid")
-                        .add_string_choice("Flagle", "flagle")
-                        .add_string_choice("FoodGuessr", "foodguessr"),
-                    ),
-                )
-                .add_option(
-                    CreateCommandOption::new(
-                        CommandOptionType::SubCommand,
-                        "all_time",
-                        "View the all-time leaderboard",
-                    )
-                    .add_sub_option(
-                        CreateCommandOption::new(
-                            CommandOptionType::String,
-                            "game",
-                            "The game to view the leaderboard for",
-                        )
-                        .required(true)
-                        .add_string_choice("GeoGrid", "geogrid")
-                        .add_string_choice("Flagle", "flagle")
-                        .add_string_choice("FoodGuessr", "foodguessr"),
-                    )
-                    .add_sub_option(CreateCommandOption::new(
-                        CommandOptionType::Boolean,
-                        "include_today",
-                        "Include today's scores in the all-time leaderboard?",
-                    ))
-                    .add_sub_option(CreateCommandOption::new(
-                        CommandOptionType::Boolean,
-                        "include_late",
-                        "Include score submissions that were entered after the day ended?",
-                    )),
-                ),
-        )
-        .await
-        {
-            Ok(_) => info!("created global /leaderboard command"),
-            Err(error) => warn!(%error, "failed to create global /leaderboard command"),
+        // Messages from now on arrive as events, so catching up only needs to go this far
+        let connected_at = history::message_id_at(Utc::now());
+
+        for (name, command) in [
+            (
+                commands::leaderboard::NAME,
+                commands::leaderboard::register(),
+            ),
+            (commands::stats::NAME, commands::stats::register()),
+            (commands::config::NAME, commands::config::register()),
+            (commands::score::NAME, commands::score::register()),
+            (commands::backfill::NAME, commands::backfill::register()),
+            (commands::scores::NAME, commands::scores::register()),
+        ] {
+            match Command::create_global_command(&ctx.http, command).await {
+                Ok(_) => info!("created global /{} command", name),
+                Err(error) => warn!(%error, "failed to create global /{} command", name),
+            }
         }
+
+        self.catch_up(&ctx, connected_at).await;
+        self.caught_up.notify_one();
     }
 
     async fn message(&self, ctx: Context, msg: Message) {
@@ -98,36 +100,116 @@ impl EventHandler for Bot {
             return;
         };
 
-        match msg.content.parse::<<GeoGrid as Game>::Score>() {
-            Ok(score) => {
-                self.process_score::<GeoGrid>(score, ctx, msg, guild_id)
-                    .await;
-                return;
+        match self.detect_score(&ctx, &msg, guild_id, (&msg).into()).await {
+            // Only channels that scores are posted in are worth catching up on, so only accepted
+            // scores move the cursor. Failures are already logged, and at worst mean a message is
+            // looked at twice
+            Some((_, Ok(_))) => {
+                let _ =
+                    persist::update_channel_cursor(&self.db_pool, guild_id, msg.channel_id, msg.id)
+                        .await;
             }
-            Err(error) => {
-                debug!(reason = %error, "message isn't a Geogrid score");
+            // Only scores that were just posted get a hint, rather than every old score that's
+            // caught up on or edited
+            Some((game, Err(Rejected::WrongChannel(channels)))) => {
+                self.hint_score_channels(&ctx, &msg, game, &channels).await;
             }
+            Some((_, Err(Rejected::Ignored))) | None => {}
         }
+    }
 
-        match msg.content.parse::<<Flagle as Game>::Score>() {
-            Ok(score) => {
-                self.process_score::<Flagle>(score, ctx, msg, guild_id)
-                    .await;
+    async fn message_update(
+        &self,
+        ctx: Context,
+        _old_if_available: Option<Message>,
+        new: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        let Some(guild_id) = event.guild_id else {
+            warn!("cannot continue processing message update without guild ID");
+            return;
+        };
+
+        if event.content.is_none() {
+            debug!("message update didn't change content, ignoring");
+            return;
+        }
+
+        let msg = match new {
+            Some(msg) => msg,
+            None => match event.channel_id.message(&ctx.http, event.id).await {
+                Ok(msg) => msg,
+                Err(error) => {
+                    error!(%error, "failed to fetch updated message");
+                    return;
+                }
+            },
+        };
+
+        // An edit that leaves the score as it was, e.g. to fix a typo in a comment, shouldn't make
+        // the score late
+        for game in GAMES {
+            if game
+                .is_unchanged(&self.db_pool, guild_id, msg.id, &msg.content)
+                .await
+            {
+                debug!(game = game.slug(), "edit didn't change the score, ignoring");
                 return;
             }
-            Err(error) => {
-                debug!(reason = %error, "message isn't a Flagle score");
-            }
         }
 
-        match msg.content.parse::<<FoodGuessr as Game>::Score>() {
-            Ok(score) => {
-                self.process_score::<FoodGuessr>(score, ctx, msg, guild_id)
-                    .await;
-                return;
+        // Otherwise the edited message might be a different score, or no longer a score at all, so
+        // start again from scratch, as if it had been posted when it was edited
+        let retracted = self.retract_score(guild_id, msg.id).await;
+        let accepted = matches!(
+            self.detect_score(&ctx, &msg, guild_id, Submission::edited(&msg))
+                .await,
+            Some((_, Ok(_)))
+        );
+
+        if let Some((game, board)) = retracted {
+            if !accepted {
+                self.remove_reaction(&ctx, &msg, reactions::RECORDED).await;
+                self.remove_reaction(&ctx, &msg, reactions::BEST).await;
             }
-            Err(error) => {
-                debug!(reason = %error, "message isn't a FoodGuessr score");
+
+            // The edit might have made the score worse, or moved it to another board
+            self.update_best(&ctx, game, guild_id, board).await;
+        }
+    }
+
+    async fn message_delete(
+        &self,
+        ctx: Context,
+        _channel_id: ChannelId,
+        deleted_message_id: MessageId,
+        guild_id: Option<GuildId>,
+    ) {
+        let Some(guild_id) = guild_id else {
+            warn!("cannot continue processing message deletion without guild ID");
+            return;
+        };
+
+        if let Some((game, board)) = self.retract_score(guild_id, deleted_message_id).await {
+            self.update_best(&ctx, game, guild_id, board).await;
+        }
+    }
+
+    async fn message_delete_bulk(
+        &self,
+        ctx: Context,
+        _channel_id: ChannelId,
+        multiple_deleted_messages_ids: Vec<MessageId>,
+        guild_id: Option<GuildId>,
+    ) {
+        let Some(guild_id) = guild_id else {
+            warn!("cannot continue processing message deletion without guild ID");
+            return;
+        };
+
+        for message_id in multiple_deleted_messages_ids {
+            if let Some((game, board)) = self.retract_score(guild_id, message_id).await {
+                self.update_best(&ctx, game, guild_id, board).await;
             }
         }
     }
@@ -135,6 +217,7 @@ impl EventHandler for Bot {
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
         async fn process_command(
             command: &CommandInteraction,
+            http: &Http,
             db_pool: &PgPool,
         ) -> CreateInteractionResponseMessage {
             let Some(guild_id) = command.guild_id else {
@@ -145,186 +228,351 @@ impl EventHandler for Bot {
 
             info!(%guild_id, "received command interaction");
 
-            let options = command.data.options();
-            let Some(ResolvedOption {
-                name,
-                value: ResolvedValue::SubCommand(options),
-                ..
-            }) = options.first()
-            else {
-                return CreateInteractionResponseMessage::new()
-                    .content("An unexpected error occurred");
-            };
+            let settings = GuildSettings::get(db_pool, guild_id)
+                .await
+                .unwrap_or_else(|error| {
+                    error!(%error, "failed to fetch guild settings, falling back to defaults");
+                    GuildSettings::default()
+                });
 
-            let Some(game) = options.iter().find_map(|opt| {
-                if let ResolvedOption {
-                    name: "game",
-                    value: ResolvedValue::String(value),
-                    ..
-                } = opt
-                {
-                    Some(*value)
-                } else {
-                    None
+            match command.data.name.as_str() {
+                commands::leaderboard::NAME => {
+                    commands::leaderboard::run(command, db_pool, guild_id, &settings).await
                 }
-            }) else {
-                warn!("cannot respond to command without a value for the game parameter");
-                return CreateInteractionResponseMessage::new()
-                    .content("You must specify a game in order to view the leaderboard!");
-            };
+                commands::stats::NAME => {
+                    commands::stats::run(command, db_pool, guild_id, &settings).await
+                }
+                commands::score::NAME => {
+                    commands::score::run(command, http, db_pool, guild_id, &settings).await
+                }
+                commands::config::NAME => commands::config::run(command, db_pool, guild_id).await,
+                _ => {
+                    CreateInteractionResponseMessage::new().content("An unexpected error occurred.")
+                }
+            }
+        }
 
-            if *name == "today" {
-                let embed = match game {
-                    "geogrid" => GeoGrid::daily_leaderboard(db_pool, guild_id)
-                        .await
-                        .map(Into::into),
-                    "flagle" => Flagle::daily_leaderboard(db_pool, guild_id)
-                        .await
-                        .map(Into::into),
-                    "foodguessr" => FoodGuessr::daily_leaderboard(db_pool, guild_id)
-                        .await
-                        .map(Into::into),
-                    _ => {
-                        return CreateInteractionResponseMessage::new()
-                            .content(format!("Unknown game \"{}\"!", game))
+        match interaction {
+            Interaction::Command(command) => {
+                match command.data.name.as_str() {
+                    commands::backfill::NAME => {
+                        commands::backfill::run(&ctx, &command, &self.db_pool).await;
+                        return;
                     }
-                };
-
-                match embed {
-                    Ok(embed) => CreateInteractionResponseMessage::new()
-                        .embed(embed)
-                        .allowed_mentions(CreateAllowedMentions::new()),
-                    Err(error) => {
-                        error!(%error, "failed to calculate daily leaderboard");
-                        CreateInteractionResponseMessage::new()
-                            .content("An unexpected error occurred.")
+                    commands::scores::NAME => {
+                        commands::scores::run(&ctx, &command, &self.db_pool).await;
+                        return;
                     }
+                    _ => {}
                 }
-            } else if *name == "all_time" {
-                let include_today = options
-                    .iter()
-                    .find_map(|opt| {
-                        if let ResolvedOption {
-                            name: "include_today",
-                            value: ResolvedValue::Boolean(value),
-                            ..
-                        } = opt
-                        {
-                            Some(*value)
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or(true);
-
-                let include_late = options
-                    .iter()
-                    .find_map(|opt| {
-                        if let ResolvedOption {
-                            name: "include_late",
-                            value: ResolvedValue::Boolean(value),
-                            ..
-                        } = opt
-                        {
-                            Some(*value)
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or(false);
 
-                let embed = match game {
-                    "geogrid" => GeoGrid::all_time_leaderboard(
-                        db_pool,
-                        guild_id,
-                        include_today,
-                        include_late,
-                    )
+                let response = process_command(&command, &ctx.http, &self.db_pool)
                     .await
-                    .map(Into::into),
-                    "flagle" => {
-                        Flagle::all_time_leaderboard(db_pool, guild_id, include_today, include_late)
-                            .await
-                            .map(Into::into)
-                    }
-                    "foodguessr" => FoodGuessr::all_time_leaderboard(
-                        db_pool,
-                        guild_id,
-                        include_today,
-                        include_late,
-                    )
-                    .await
-                    .map(Into::into),
-                    _ => {
-                        return CreateInteractionResponseMessage::new()
-                            .content(format!("Unknown game \"{}\"!", game))
+                    .pipe(CreateInteractionResponse::Message);
+
+                match command.create_response(&ctx.http, response).await {
+                    Ok(_) => info!("responded to command"),
+                    Err(error) => error!(%error, "failed to respond to command"),
+                }
+            }
+            Interaction::Modal(modal) if commands::scores::is_own_modal(&modal) => {
+                commands::scores::submit(&ctx, &modal, &self.db_pool).await;
+            }
+            Interaction::Component(component)
+                if commands::leaderboard::is_own_component(&component) =>
+            {
+                commands::leaderboard::turn_page(&ctx, &component, &self.db_pool).await;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Bot {
+    /// Process any messages that were posted while the bot was offline, in every channel that it
+    /// has seen scores in before, going back at most [`MAX_CATCH_UP`]. Messages from `connected_at`
+    /// onwards are left to the live event handlers, so that nothing is processed twice. Ratings are
+    /// worked out again from the earliest board that a missed score was recorded for.
+    #[instrument(skip_all)]
+    async fn catch_up(&self, ctx: &Context, connected_at: MessageId) {
+        let cursors = match persist::get_channel_cursors(&self.db_pool).await {
+            Ok(cursors) => cursors,
+            Err(error) => {
+                error!(%error, "failed to fetch channel cursors");
+                return;
+            }
+        };
+
+        let earliest = history::message_id_at(Utc::now() - MAX_CATCH_UP);
+        let mut earliest_boards = HashMap::<(GuildId, &str), (&'static dyn DynGame, usize)>::new();
+
+        for cursor in cursors {
+            let guild_id = GuildId::new(cursor.guild_id as u64);
+            let channel_id = ChannelId::new(cursor.channel_id as u64);
+            let last_message_id = MessageId::new(cursor.last_message_id as u64).max(earliest);
+
+            let mut history = History::after(channel_id, last_message_id);
+            let mut caught_up = 0;
+
+            'pages: loop {
+                let messages = match history.next_page(&ctx.http).await {
+                    Ok(messages) if messages.is_empty() => break,
+                    Ok(messages) => messages,
+                    Err(error) => {
+                        warn!(%error, %channel_id, "failed to fetch missed messages");
+                        break;
                     }
                 };
 
-                match embed {
-                    Ok(embed) => CreateInteractionResponseMessage::new()
-                        .embed(embed)
-                        .allowed_mentions(CreateAllowedMentions::new()),
-                    Err(error) => {
-                        error!(%error, "failed to calculate all-time leaderboard");
-                        CreateInteractionResponseMessage::new()
-                            .content("An unexpected error occurred.")
+                for msg in messages {
+                    if msg.id >= connected_at {
+                        break 'pages;
+                    }
+
+                    if let Some((game, Ok(accepted))) =
+                        self.detect_score(ctx, &msg, guild_id, (&msg).into()).await
+                    {
+                        let _ = persist::update_channel_cursor(
+                            &self.db_pool,
+                            guild_id,
+                            channel_id,
+                            msg.id,
+                        )
+                        .await;
+
+                        if let Accepted::Recorded(board) = accepted {
+                            earliest_boards
+                                .entry((guild_id, game.slug()))
+                                .and_modify(|(_, first)| *first = (*first).min(board))
+                                .or_insert((game, board));
+                        }
                     }
+                    caught_up += 1;
                 }
-            } else {
-                CreateInteractionResponseMessage::new().content("An unexpected error occurred.")
+            }
+
+            if caught_up > 0 {
+                info!(%channel_id, caught_up, "caught up on missed messages");
             }
         }
 
-        if let Interaction::Command(command) = interaction {
-            let response = process_command(&command, &self.db_pool)
+        for ((guild_id, _), (game, board)) in earliest_boards {
+            rating::board_changed(&self.db_pool, guild_id, game, board).await;
+        }
+    }
+
+    /// Try to parse `msg` as a score for each game in turn, and process it as posted at
+    /// `submission` as soon as one succeeds. Returns the game that accepted the message as a score
+    /// and how, or else the first game that the message is a score for and why it was rejected.
+    async fn detect_score(
+        &self,
+        ctx: &Context,
+        msg: &Message,
+        guild_id: GuildId,
+        submission: Submission,
+    ) -> Option<(&'static dyn DynGame, Result<Accepted, Rejected>)> {
+        let mut rejected = None;
+
+        for game in GAMES {
+            match game
+                .detect_score(self, ctx, msg, guild_id, submission)
                 .await
-                .pipe(CreateInteractionResponse::Message);
+            {
+                Some(Ok(accepted)) => return Some((*game, Ok(accepted))),
+                Some(Err(rejection)) => {
+                    rejected.get_or_insert((*game, Err(rejection)));
+                }
+                None => {}
+            }
+        }
+
+        rejected
+    }
 
-            match command.create_response(&ctx.http, response).await {
-                Ok(_) => info!("responded to command"),
-                Err(error) => error!(%error, "failed to respond to command"),
+    /// Reply to `msg` to point out the `channels` that `game`'s scores are recorded in.
+    async fn hint_score_channels(
+        &self,
+        ctx: &Context,
+        msg: &Message,
+        game: &dyn DynGame,
+        channels: &[ChannelId],
+    ) {
+        let channels = channels
+            .iter()
+            .map(|&channel_id| Mention::Channel(channel_id).to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let hint = format!(
+            "{} scores aren't recorded in this channel. Please post them in {} instead!",
+            game.display_name(),
+            channels,
+        );
+
+        match msg.reply(&ctx.http, hint).await {
+            Ok(_) => info!("responded to score with hint about score channels"),
+            Err(error) => {
+                error!(%error, "failed to respond with hint about score channels")
             }
         }
     }
-}
 
-impl Bot {
-    #[instrument(skip_all, fields(game = %G::description(), %guild_id))]
-    async fn process_score<G>(&self, score: G::Score, ctx: Context, msg: Message, guild_id: GuildId)
+    /// Delete any score that was recorded from the message with `message_id`. Returns the game
+    /// and board of the deleted score.
+    #[instrument(skip(self))]
+    async fn retract_score(
+        &self,
+        guild_id: GuildId,
+        message_id: MessageId,
+    ) -> Option<(&'static dyn DynGame, usize)> {
+        for game in GAMES {
+            match game
+                .retract_score(&self.db_pool, guild_id, message_id)
+                .await
+            {
+                Ok(Some(board)) => {
+                    info!(game = %game.display_name(), board, "retracted score");
+                    rating::board_changed(&self.db_pool, guild_id, *game, board).await;
+                    return Some((*game, board));
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    error!(%error, game = %game.display_name(), "failed to retract score");
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Move the "best so far" reaction to the best remaining score for `board`, unless reactions
+    /// are turned off in this guild.
+    async fn update_best(
+        &self,
+        ctx: &Context,
+        game: &dyn DynGame,
+        guild_id: GuildId,
+        board: usize,
+    ) {
+        let settings = GuildSettings::get(&self.db_pool, guild_id)
+            .await
+            .unwrap_or_else(|error| {
+                error!(%error, "failed to fetch guild settings, falling back to defaults");
+                GuildSettings::default()
+            });
+
+        if settings.reactions_enabled {
+            reactions::update_best(&ctx.http, &self.db_pool, game, guild_id, board).await;
+        }
+    }
+
+    async fn remove_reaction(&self, ctx: &Context, msg: &Message, reaction: char) {
+        reactions::remove(&ctx.http, msg.channel_id, msg.id, reaction).await;
+    }
+
+    /// Record `score` from `msg` and react to it. Returns how it was accepted, or why it wasn't, e.g.
+    /// because the game is disabled, scores aren't recorded in the message's channel or the score
+    /// couldn't be recorded.
+    #[instrument(skip_all, fields(game = %G::display_name(), %guild_id))]
+    pub(crate) async fn process_score<G>(
+        &self,
+        score: G::Score,
+        ctx: &Context,
+        msg: &Message,
+        guild_id: GuildId,
+        submission: Submission,
+    ) -> Result<Accepted, Rejected>
     where
         G: Game,
     {
         info!(?score, "processing score");
 
-        match score.insert(&self.db_pool, guild_id, &msg.author).await {
+        let settings = GuildSettings::get(&self.db_pool, guild_id)
+            .await
+            .unwrap_or_else(|error| {
+                error!(%error, "failed to fetch guild settings, falling back to defaults");
+                GuildSettings::default()
+            });
+
+        if !settings.game_enabled(G::slug()) {
+            info!("game is disabled in this guild, ignoring score");
+            return Err(Rejected::Ignored);
+        }
+
+        if !settings.accepts_scores_in(G::slug(), msg.channel_id, None) {
+            // The channel itself isn't allowed, but it might be in an allowed category (or be a
+            // thread in an allowed channel)
+            let parent_id = match msg.channel_id.to_channel(&ctx.http).await {
+                Ok(Channel::Guild(channel)) => channel.parent_id,
+                Ok(_) => None,
+                Err(error) => {
+                    warn!(%error, "failed to fetch channel to check its parent");
+                    None
+                }
+            };
+
+            if !settings.accepts_scores_in(G::slug(), msg.channel_id, parent_id) {
+                info!(
+                    channel_id = %msg.channel_id,
+                    "scores aren't accepted in this channel, ignoring score"
+                );
+
+                return Err(Rejected::WrongChannel(
+                    settings.score_channels_for(G::slug()),
+                ));
+            }
+        }
+
+        match score
+            .insert(&self.db_pool, guild_id, &msg.author, submission)
+            .await
+        {
+            Ok(inserted_score) if !settings.reactions_enabled => {
+                info!("recorded score without reacting");
+                Ok(Accepted::Recorded(inserted_score.board()))
+            }
+            Err(ScoreInsertionError::Duplicate) if !settings.reactions_enabled => {
+                info!("ignored duplicate score without reacting");
+                Ok(Accepted::Duplicate)
+            }
             Ok(inserted_score) => {
-                match msg.react(&ctx.http, '✅').await {
-                    Ok(_) => info!(reaction = %'✅', "reacted to new score"),
+                match msg.react(&ctx.http, reactions::RECORDED).await {
+                    Ok(_) => info!(reaction = %reactions::RECORDED, "reacted to new score"),
                     Err(error) => {
-                        error!(%error, reaction = %'✅', "failed to react to new score")
+                        error!(
+                            %error,
+                            reaction = %reactions::RECORDED,
+                            "failed to react to new score"
+                        )
                     }
                 }
 
                 if inserted_score.is_best_so_far() && inserted_score.is_on_time() {
-                    match msg.react(&ctx.http, '✨').await {
-                        Ok(_) => info!(reaction = %'✨', "reacted to today's best score"),
-                        Err(error) => {
-                            error!(
-                                %error,
-                                reaction = %'✨',
-                                "failed to react to today's best score"
-                            )
-                        }
-                    }
+                    reactions::update_best(
+                        &ctx.http,
+                        &self.db_pool,
+                        &Registered::<G>::new(),
+                        guild_id,
+                        inserted_score.board(),
+                    )
+                    .await;
                 }
+
+                Ok(Accepted::Recorded(inserted_score.board()))
             }
-            Err(ScoreInsertionError::Duplicate) => match msg.react(&ctx.http, '🗞').await {
-                Ok(_) => info!(reaction = %'🗞', "reacted to duplicate score"),
-                Err(error) => {
-                    error!(%error, reaction = %'🗞', "failed to react to duplicate score")
+            Err(ScoreInsertionError::Duplicate) => {
+                match msg.react(&ctx.http, reactions::DUPLICATE).await {
+                    Ok(_) => info!(reaction = %reactions::DUPLICATE, "reacted to duplicate score"),
+                    Err(error) => {
+                        error!(
+                            %error,
+                            reaction = %reactions::DUPLICATE,
+                            "failed to react to duplicate score"
+                        )
+                    }
                 }
-            },
+
+                Ok(Accepted::Duplicate)
+            }
             Err(error) => {
                 error!(%error, "failed to insert score");
 
@@ -343,6 +591,8 @@ impl Bot {
                         )
                     }
                 }
+
+                Err(Rejected::Ignored)
             }
         }
     }
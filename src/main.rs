@@ -1,10 +1,11 @@
-use std::env;
+use std::{env, sync::Arc};
 
 use serenity::{gateway::ActivityData, prelude::*};
 use sqlx::PgPool;
+use tokio::sync::Notify;
 use tracing::{error, info, level_filters::LevelFilter};
 use tracing_subscriber::{fmt, layer::SubscriberExt as _, util::SubscriberInitExt as _, EnvFilter};
-use vexillologist::Bot;
+use vexillologist::{schedule, Bot};
 
 #[tokio::main]
 async fn main() {
@@ -58,11 +59,22 @@ async fn main() {
     // Set gateway intents, which decides what events the bot will be notified about
     let intents = GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT;
 
+    let caught_up = Arc::new(Notify::new());
+
     let mut client = Client::builder(&discord_token, intents)
-        .event_handler(Bot { db_pool })
+        .event_handler(Bot {
+            db_pool: db_pool.clone(),
+            caught_up: caught_up.clone(),
+        })
         .activity(ActivityData::custom("Watching for scores"))
         .await
         .expect("should have constructed client");
 
+    tokio::spawn(schedule::post_results(
+        client.http.clone(),
+        db_pool,
+        caught_up,
+    ));
+
     client.start().await.unwrap();
 }
@@ -1,8 +1,8 @@
 use std::fmt;
 
 use indoc::indoc;
-use serenity::all::{GuildId, User};
-use sqlx::{Error as SqlxError, FromRow, Postgres, Transaction};
+use serenity::all::{ChannelId, GuildId, MessageId, User};
+use sqlx::{Error as SqlxError, FromRow, PgPool, Postgres, Transaction};
 use thiserror::Error;
 use tracing::{debug, error, info};
 
@@ -22,6 +22,13 @@ pub struct GuildUserRow {
     pub user_id: i64,
 }
 
+#[derive(Clone, Debug, FromRow)]
+pub struct ChannelCursorRow {
+    pub channel_id: i64,
+    pub guild_id: i64,
+    pub last_message_id: i64,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum InsertionTarget {
     Guild,
@@ -41,10 +48,9 @@ impl fmt::Display for InsertionTarget {
     }
 }
 
-pub async fn insert_guild_user(
+pub async fn insert_guild(
     txn: &mut Transaction<'_, Postgres>,
     guild_id: GuildId,
-    user: &User,
 ) -> Result<(), GuildUserInsertionError> {
     let insert_guilds = sqlx::query(indoc! {"
         INSERT INTO guilds (guild_id)
@@ -73,6 +79,16 @@ pub async fn insert_guild_user(
         }
     }
 
+    Ok(())
+}
+
+pub async fn insert_guild_user(
+    txn: &mut Transaction<'_, Postgres>,
+    guild_id: GuildId,
+    user: &User,
+) -> Result<(), GuildUserInsertionError> {
+    insert_guild(txn, guild_id).await?;
+
     let insert_users = sqlx::query(indoc! {"
         INSERT INTO users (user_id)
         VALUES ($1)
@@ -127,3 +143,153 @@ pub enum GuildUserInsertionError {
         error: SqlxError,
     },
 }
+
+/// Record that every message in `channel_id` up to and including `message_id` has been processed.
+pub async fn update_channel_cursor(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    message_id: MessageId,
+) -> Result<(), SqlxError> {
+    let upsert_cursor = sqlx::query(indoc! {"
+        INSERT INTO channel_cursors (channel_id, guild_id, last_message_id)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (channel_id) DO UPDATE
+        SET last_message_id = GREATEST(channel_cursors.last_message_id, EXCLUDED.last_message_id);
+    "});
+    match upsert_cursor
+        .bind(channel_id.get() as i64)
+        .bind(guild_id.get() as i64)
+        .bind(message_id.get() as i64)
+        .execute(db_pool)
+        .await
+    {
+        Ok(_) => {
+            debug!(%channel_id, %message_id, "updated channel cursor");
+            Ok(())
+        }
+        Err(error) => {
+            error!(%error, "failed to update channel cursor");
+            Err(error)
+        }
+    }
+}
+
+pub async fn get_channel_cursors(db_pool: &PgPool) -> Result<Vec<ChannelCursorRow>, SqlxError> {
+    sqlx::query_as::<_, ChannelCursorRow>(indoc! {"
+        SELECT channel_id, guild_id, last_message_id
+        FROM channel_cursors;
+    "})
+    .fetch_all(db_pool)
+    .await
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct BestScoreRow {
+    pub channel_id: i64,
+    pub message_id: i64,
+}
+
+/// Get the messages that currently have the "best so far" reaction for a board.
+pub async fn get_best_scores(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    game: &str,
+    board: usize,
+) -> Result<Vec<(ChannelId, MessageId)>, SqlxError> {
+    let get_best_scores = sqlx::query_as::<_, BestScoreRow>(indoc! {"
+        SELECT
+            channel_id,
+            message_id
+        FROM best_scores
+        WHERE
+            guild_id = $1
+            AND game = $2
+            AND board = $3
+        ORDER BY message_id;
+    "});
+    match get_best_scores
+        .bind(guild_id.get() as i64)
+        .bind(game)
+        .bind(board as i32)
+        .fetch_all(db_pool)
+        .await
+    {
+        Ok(rows) => Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    ChannelId::new(row.channel_id as u64),
+                    MessageId::new(row.message_id as u64),
+                )
+            })
+            .collect()),
+        Err(error) => {
+            error!(%error, "failed to get best score messages");
+            Err(error)
+        }
+    }
+}
+
+/// Record which messages now have the "best so far" reaction for a board, replacing any that had
+/// it before.
+pub async fn set_best_scores(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    game: &str,
+    board: usize,
+    best: &[(ChannelId, MessageId)],
+) -> Result<(), SqlxError> {
+    let mut txn = db_pool.begin().await?;
+
+    let delete_best_scores = sqlx::query(indoc! {"
+        DELETE FROM best_scores
+        WHERE
+            guild_id = $1
+            AND game = $2
+            AND board = $3;
+    "});
+    let insert_best_scores = sqlx::query(indoc! {"
+        INSERT INTO best_scores (guild_id, game, board, channel_id, message_id)
+        SELECT $1, $2, $3, b.*
+        FROM UNNEST($4::BIGINT[], $5::BIGINT[]) b;
+    "});
+
+    let result = async {
+        delete_best_scores
+            .bind(guild_id.get() as i64)
+            .bind(game)
+            .bind(board as i32)
+            .execute(txn.as_mut())
+            .await?;
+        insert_best_scores
+            .bind(guild_id.get() as i64)
+            .bind(game)
+            .bind(board as i32)
+            .bind(
+                best.iter()
+                    .map(|(channel_id, _)| channel_id.get() as i64)
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                best.iter()
+                    .map(|(_, message_id)| message_id.get() as i64)
+                    .collect::<Vec<_>>(),
+            )
+            .execute(txn.as_mut())
+            .await?;
+        txn.commit().await
+    }
+    .await;
+
+    match result {
+        Ok(()) => {
+            debug!(%guild_id, game, board, "updated best score messages");
+            Ok(())
+        }
+        Err(error) => {
+            error!(%error, "failed to update best score messages");
+            Err(error)
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use serenity::all::{ChannelId, GuildId, Http, MessageId};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+use crate::{
+    game::{DynGame, ScoreMessage},
+    persist,
+};
+
+/// Reaction for a score that was recorded.
+pub const RECORDED: char = '✅';
+
+/// Reaction for the best on-time score for a board so far.
+pub const BEST: char = '✨';
+
+/// Reaction for a score that had already been recorded.
+pub const DUPLICATE: char = '🗞';
+
+pub async fn add(http: &Http, channel_id: ChannelId, message_id: MessageId, reaction: char) {
+    match channel_id.create_reaction(http, message_id, reaction).await {
+        Ok(_) => info!(%reaction, %message_id, "added reaction"),
+        Err(error) => warn!(%error, %reaction, %message_id, "failed to add reaction"),
+    }
+}
+
+/// Remove the bot's own `reaction` from a message, leaving anyone else's in place.
+pub async fn remove(http: &Http, channel_id: ChannelId, message_id: MessageId, reaction: char) {
+    match channel_id
+        .delete_reaction(http, message_id, None, reaction)
+        .await
+    {
+        Ok(_) => info!(%reaction, %message_id, "removed reaction"),
+        Err(error) => warn!(%error, %reaction, %message_id, "failed to remove reaction"),
+    }
+}
+
+/// Make sure that [`BEST`] is on the messages holding the best on-time score for `board`, and on
+/// no other message for that board. Tied scores share the reaction, unless the guild breaks ties by
+/// time, in which case a later tie never takes it away.
+pub async fn update_best(
+    http: &Http,
+    db_pool: &PgPool,
+    game: &dyn DynGame,
+    guild_id: GuildId,
+    board: usize,
+) {
+    let mut best = match game.best_scores(db_pool, guild_id, board).await {
+        Ok(best) => best
+            .into_iter()
+            .filter_map(|best| Some((best.channel_id?, best.message_id?)))
+            .collect::<Vec<_>>(),
+        Err(error) => {
+            error!(%error, board, "failed to find best scores");
+            return;
+        }
+    };
+    best.sort_unstable_by_key(|(_, message_id)| *message_id);
+
+    let Ok(previous) = persist::get_best_scores(db_pool, guild_id, game.slug(), board).await else {
+        return;
+    };
+
+    if previous == best {
+        return;
+    }
+
+    for &(channel_id, message_id) in previous.iter().filter(|message| !best.contains(message)) {
+        remove(http, channel_id, message_id, BEST).await;
+    }
+    for &(channel_id, message_id) in best.iter().filter(|message| !previous.contains(message)) {
+        add(http, channel_id, message_id, BEST).await;
+    }
+
+    if persist::set_best_scores(db_pool, guild_id, game.slug(), board, &best)
+        .await
+        .is_ok()
+    {
+        info!(board, ?best, ?previous, "moved best score reactions");
+    }
+}
+
+/// Clean up the reactions on the message that a withdrawn score was submitted in, and pass
+/// [`BEST`] on to whichever score is now the best for its board.
+pub async fn withdraw(
+    http: &Http,
+    db_pool: &PgPool,
+    game: &dyn DynGame,
+    guild_id: GuildId,
+    board: usize,
+    withdrawn: ScoreMessage,
+) {
+    if let ScoreMessage {
+        message_id: Some(message_id),
+        channel_id: Some(channel_id),
+    } = withdrawn
+    {
+        remove(http, channel_id, message_id, RECORDED).await;
+        remove(http, channel_id, message_id, BEST).await;
+    }
+
+    update_best(http, db_pool, game, guild_id, board).await;
+}
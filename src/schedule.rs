@@ -0,0 +1,239 @@
+use std::{sync::Arc, time::Duration};
+
+use indoc::{formatdoc, indoc};
+use serenity::all::{ChannelId, CreateAllowedMentions, CreateMessage, GuildId, Http};
+use sqlx::{FromRow, PgPool};
+use tokio::sync::Notify;
+use tracing::{error, info, instrument, warn};
+
+use crate::{
+    commands::leaderboard,
+    game::{rating, DynGame, ScoreTable, GAMES},
+};
+
+/// How often to check whether a game's day has ended. Results are posted at most this long after
+/// each game's board rolls over.
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Post the final daily leaderboard for each game into every guild's results channel, and count
+/// the board towards everyone's ratings, shortly after the game's board rolls over. Runs forever,
+/// starting once `caught_up` is first notified, so that boards which closed while the bot was
+/// offline include the scores that were missed.
+pub async fn post_results(http: Arc<Http>, db_pool: PgPool, caught_up: Arc<Notify>) {
+    caught_up.notified().await;
+
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        for game in GAMES {
+            post_results_for(*game, &http, &db_pool).await;
+            update_ratings_for(*game, &db_pool).await;
+        }
+    }
+}
+
+#[instrument(skip_all, fields(game = %game.display_name()))]
+async fn post_results_for(game: &'static dyn DynGame, http: &Http, db_pool: &PgPool) {
+    let Some(board) = game.board_now().checked_sub(1) else {
+        return;
+    };
+
+    let get_guilds_string = guilds_awaiting_results(&game.score_table());
+    let get_guilds = sqlx::query(get_guilds_string.as_ref());
+    let rows = match get_guilds
+        .bind(game.slug())
+        .bind(board as i32)
+        .fetch_all(db_pool)
+        .await
+    {
+        Ok(rows) => rows,
+        Err(error) => {
+            error!(%error, "failed to fetch guilds awaiting results");
+            return;
+        }
+    };
+
+    for row in rows {
+        let row = match ResultsChannelRow::from_row(&row) {
+            Ok(row) => row,
+            Err(error) => {
+                error!(%error, "failed to extract data from row");
+                continue;
+            }
+        };
+
+        let guild_id = GuildId::new(row.guild_id as u64);
+        let channel_id = ChannelId::new(row.results_channel_id as u64);
+
+        // Claim the post before sending it, so that a failure to send (e.g. because of missing
+        // permissions) doesn't cause a retry every minute for the rest of the day
+        let claim_post = sqlx::query(indoc! {"
+            INSERT INTO results_posts (guild_id, game, board)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING;
+        "});
+        match claim_post
+            .bind(row.guild_id)
+            .bind(game.slug())
+            .bind(board as i32)
+            .execute(db_pool)
+            .await
+        {
+            Ok(result) if result.rows_affected() > 0 => {}
+            Ok(_) => continue,
+            Err(error) => {
+                error!(%error, %guild_id, "failed to record results post");
+                continue;
+            }
+        }
+
+        let listing = match game.daily_leaderboard(db_pool, guild_id, board).await {
+            Ok(listing) => listing,
+            Err(error) => {
+                error!(%error, %guild_id, "failed to calculate daily leaderboard");
+                continue;
+            }
+        };
+
+        let message = CreateMessage::new()
+            .content(format!(
+                "Final results for the last {} board:",
+                game.display_name()
+            ))
+            .embed(listing.page(0))
+            .components(leaderboard::buttons(
+                &listing,
+                0,
+                None,
+                leaderboard::Query::Daily { game, board },
+            ))
+            .allowed_mentions(CreateAllowedMentions::new());
+
+        match channel_id.send_message(http, message).await {
+            Ok(_) => info!(%guild_id, %channel_id, board, "posted results"),
+            Err(error) => warn!(%error, %guild_id, %channel_id, "failed to post results"),
+        }
+    }
+}
+
+/// The query for guilds that should get a results post for board `$2` of game `$1`. Only guilds
+/// where somebody played the board on time, and didn't fail it, are worth posting in, and each
+/// guild only gets one post per board, even across restarts.
+fn guilds_awaiting_results(table: &ScoreTable) -> String {
+    formatdoc!(
+        "
+        SELECT
+            gs.guild_id,
+            gs.results_channel_id
+        FROM guild_settings gs
+        WHERE
+            gs.results_channel_id IS NOT NULL
+            AND EXISTS (
+                SELECT 1
+                FROM {name} s
+                WHERE
+                    s.guild_id = gs.guild_id
+                    AND {board} = $2
+                    AND ({on_time})
+                    AND ({ranked})
+            )
+            AND NOT EXISTS (
+                SELECT 1
+                FROM guild_disabled_games d
+                WHERE
+                    d.guild_id = gs.guild_id
+                    AND d.game = $1
+            )
+            AND NOT EXISTS (
+                SELECT 1
+                FROM results_posts p
+                WHERE
+                    p.guild_id = gs.guild_id
+                    AND p.game = $1
+                    AND p.board = $2
+            );
+        ",
+        name = table.name,
+        board = table.board,
+        on_time = table.on_time,
+        ranked = table.ranked,
+    )
+}
+
+#[instrument(skip_all, fields(game = %game.display_name()))]
+async fn update_ratings_for(game: &'static dyn DynGame, db_pool: &PgPool) {
+    let Some(board) = game.board_now().checked_sub(1) else {
+        return;
+    };
+
+    let table = game.score_table();
+
+    // Only guilds with scores on boards that haven't been counted yet need updating
+    let get_guilds_string = formatdoc!(
+        "
+        SELECT g.guild_id
+        FROM
+            guilds g
+            LEFT JOIN rating_progress p ON p.guild_id = g.guild_id AND p.game = $1
+        WHERE
+            COALESCE(p.last_board, -1) < $2
+            AND EXISTS (
+                SELECT 1
+                FROM {name} s
+                WHERE
+                    s.guild_id = g.guild_id
+                    AND {board} > COALESCE(p.last_board, -1)
+                    AND {board} <= $2
+            );
+        ",
+        name = table.name,
+        board = table.board,
+    );
+    let get_guilds = sqlx::query_scalar::<_, i64>(get_guilds_string.as_ref());
+    let guild_ids = match get_guilds
+        .bind(game.slug())
+        .bind(board as i32)
+        .fetch_all(db_pool)
+        .await
+    {
+        Ok(guild_ids) => guild_ids,
+        Err(error) => {
+            error!(%error, "failed to fetch guilds awaiting ratings");
+            return;
+        }
+    };
+
+    for guild_id in guild_ids {
+        let guild_id = GuildId::new(guild_id as u64);
+        match rating::update(db_pool, guild_id, game).await {
+            Ok(()) => info!(%guild_id, board, "updated ratings"),
+            Err(error) => error!(%error, %guild_id, "failed to update ratings"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+struct ResultsChannelRow {
+    guild_id: i64,
+    results_channel_id: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::guilds_awaiting_results;
+    use crate::game::{wordle::Wordle, Game};
+
+    #[test]
+    fn results_are_not_posted_for_all_failed_boards() {
+        let query = guilds_awaiting_results(&Wordle::SCORE_TABLE);
+        let (scores, _) = query
+            .split_once("guild_disabled_games")
+            .expect("query should check for disabled games");
+
+        // A guild only counts as having played the board if one of its scores is ranked, so a
+        // board that everyone failed doesn't get an empty results post
+        assert!(scores.contains(&format!("AND ({})", Wordle::SCORE_TABLE.ranked)));
+    }
+}
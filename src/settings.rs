@@ -0,0 +1,549 @@
+use indoc::indoc;
+use serenity::all::{ChannelId, GuildId};
+use sqlx::{postgres::PgArguments, query::Query, Error as SqlxError, FromRow, PgPool, Postgres};
+use thiserror::Error;
+use tracing::{debug, error, info};
+
+use crate::{
+    game::{leaderboards::RankingStrategy, GAMES},
+    persist::{insert_guild, GuildUserInsertionError},
+};
+
+/// Everything that can be configured per guild with `/config`. Guilds that have never been
+/// configured get the defaults: every game enabled, scores accepted in every channel, reactions on,
+/// no results posts, tied Flagle scores sharing a place, and medal points for all-time
+/// leaderboards.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuildSettings {
+    pub results_channel: Option<ChannelId>,
+    pub reactions_enabled: bool,
+    pub disabled_games: Vec<String>,
+    pub score_channels: Vec<ScoreChannel>,
+
+    /// Whether tied Flagle scores are ranked by who submitted first, since with only seven
+    /// possible scores most of each day's leaderboard would otherwise be ties.
+    pub flagle_tiebreak_by_time: bool,
+
+    /// How all-time and windowed leaderboards rank players by their medals.
+    pub ranking: RankingStrategy,
+}
+
+/// A channel (or category of channels) that scores are accepted in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScoreChannel {
+    pub channel_id: ChannelId,
+
+    /// The slug of the only game whose scores are accepted, or `None` for every game.
+    pub game: Option<String>,
+}
+
+impl Default for GuildSettings {
+    fn default() -> Self {
+        Self {
+            results_channel: None,
+            reactions_enabled: true,
+            disabled_games: Vec::new(),
+            score_channels: Vec::new(),
+            flagle_tiebreak_by_time: false,
+            ranking: RankingStrategy::default(),
+        }
+    }
+}
+
+impl GuildSettings {
+    pub async fn get(db_pool: &PgPool, guild_id: GuildId) -> Result<Self, SettingsError> {
+        let get_settings = sqlx::query(indoc! {"
+            SELECT
+                gs.results_channel_id,
+                COALESCE(gs.reactions_enabled, TRUE) AS reactions_enabled,
+                COALESCE(gs.flagle_tiebreak_by_time, FALSE) AS flagle_tiebreak_by_time,
+                COALESCE(gs.ranking_strategy, 'points') AS ranking_strategy,
+                COALESCE(gs.gold_points, 4) AS gold_points,
+                COALESCE(gs.silver_points, 2) AS silver_points,
+                COALESCE(gs.bronze_points, 1) AS bronze_points,
+                ARRAY(
+                    SELECT d.game
+                    FROM guild_disabled_games d
+                    WHERE d.guild_id = g.guild_id
+                    ORDER BY d.game
+                ) AS disabled_games,
+                ARRAY(
+                    SELECT c.channel_id
+                    FROM guild_score_channels c
+                    WHERE c.guild_id = g.guild_id
+                    ORDER BY c.channel_id, c.game
+                ) AS score_channel_ids,
+                ARRAY(
+                    SELECT c.game
+                    FROM guild_score_channels c
+                    WHERE c.guild_id = g.guild_id
+                    ORDER BY c.channel_id, c.game
+                ) AS score_channel_games
+            FROM
+                (SELECT $1::BIGINT AS guild_id) g
+                LEFT JOIN guild_settings gs USING (guild_id);
+        "});
+        match get_settings
+            .bind(guild_id.get() as i64)
+            .fetch_one(db_pool)
+            .await
+        {
+            Ok(row) => {
+                let row = SettingsQueryRow::from_row(&row).map_err(SettingsError::FromRow)?;
+                debug!(?row, "fetched guild settings");
+                Ok(row.into())
+            }
+            Err(error) => {
+                error!(%error, "failed to fetch guild settings");
+                Err(SettingsError::Unexpected(error))
+            }
+        }
+    }
+
+    pub fn game_enabled(&self, slug: &str) -> bool {
+        !self.disabled_games.iter().any(|disabled| disabled == slug)
+    }
+
+    /// The channels and categories that scores for the game with `slug` are accepted in. If this is
+    /// empty, they're accepted everywhere.
+    pub fn score_channels_for(&self, slug: &str) -> Vec<ChannelId> {
+        self.score_channels
+            .iter()
+            .filter(|channel| channel.game.as_deref().map_or(true, |game| game == slug))
+            .map(|channel| channel.channel_id)
+            .collect()
+    }
+
+    /// Whether scores for the game with `slug` should be recorded when posted in `channel_id`,
+    /// which is in the category (or, for threads, the channel) `parent_id`.
+    pub fn accepts_scores_in(
+        &self,
+        slug: &str,
+        channel_id: ChannelId,
+        parent_id: Option<ChannelId>,
+    ) -> bool {
+        let allowed = self.score_channels_for(slug);
+        allowed.is_empty()
+            || allowed.contains(&channel_id)
+            || parent_id.is_some_and(|parent_id| allowed.contains(&parent_id))
+    }
+}
+
+impl From<SettingsQueryRow> for GuildSettings {
+    fn from(row: SettingsQueryRow) -> Self {
+        Self {
+            results_channel: row
+                .results_channel_id
+                .map(|channel_id| ChannelId::new(channel_id as u64)),
+            reactions_enabled: row.reactions_enabled,
+            disabled_games: row.disabled_games,
+            score_channels: row
+                .score_channel_ids
+                .into_iter()
+                .zip(row.score_channel_games)
+                .map(|(channel_id, game)| ScoreChannel {
+                    channel_id: ChannelId::new(channel_id as u64),
+                    game,
+                })
+                .collect(),
+            flagle_tiebreak_by_time: row.flagle_tiebreak_by_time,
+            ranking: RankingStrategy::from_slug(
+                &row.ranking_strategy,
+                (
+                    row.gold_points as usize,
+                    row.silver_points as usize,
+                    row.bronze_points as usize,
+                ),
+            )
+            .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+struct SettingsQueryRow {
+    results_channel_id: Option<i64>,
+    reactions_enabled: bool,
+    disabled_games: Vec<String>,
+    score_channel_ids: Vec<i64>,
+    score_channel_games: Vec<Option<String>>,
+    flagle_tiebreak_by_time: bool,
+    ranking_strategy: String,
+    gold_points: i32,
+    silver_points: i32,
+    bronze_points: i32,
+}
+
+/// Set (or clear, if `channel_id` is `None`) the channel that end-of-day results are posted in.
+pub async fn set_results_channel(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    channel_id: Option<ChannelId>,
+) -> Result<(), SettingsError> {
+    let upsert_settings = sqlx::query(indoc! {"
+        INSERT INTO guild_settings (guild_id, results_channel_id)
+        VALUES ($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE
+        SET results_channel_id = EXCLUDED.results_channel_id;
+    "})
+    .bind(guild_id.get() as i64)
+    .bind(channel_id.map(|channel_id| channel_id.get() as i64));
+
+    let mut txn = db_pool.begin().await.map_err(SettingsError::BeginTxn)?;
+
+    insert_guild(&mut txn, guild_id).await?;
+
+    if let Err(error) = upsert_settings.execute(txn.as_mut()).await {
+        error!(%error, "failed to update guild settings");
+        return Err(SettingsError::Unexpected(error));
+    }
+
+    // Boards that closed before the channel was set shouldn't be posted the next time results are
+    // checked, so count them as already posted
+    if channel_id.is_some() {
+        let (games, boards): (Vec<_>, Vec<_>) = GAMES
+            .iter()
+            .filter_map(|game| Some((game.slug(), game.board_now().checked_sub(1)? as i32)))
+            .unzip();
+
+        let seed_posts = sqlx::query(indoc! {"
+            INSERT INTO results_posts (guild_id, game, board)
+            SELECT $1, p.*
+            FROM UNNEST($2::TEXT[], $3::INTEGER[]) p
+            ON CONFLICT DO NOTHING;
+        "});
+        if let Err(error) = seed_posts
+            .bind(guild_id.get() as i64)
+            .bind(games)
+            .bind(boards)
+            .execute(txn.as_mut())
+            .await
+        {
+            error!(%error, "failed to skip results for boards that have already closed");
+            return Err(SettingsError::Unexpected(error));
+        }
+    }
+
+    txn.commit().await.map_err(SettingsError::CommitTxn)?;
+
+    info!(?channel_id, "updated results channel");
+    Ok(())
+}
+
+pub async fn set_reactions_enabled(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    enabled: bool,
+) -> Result<(), SettingsError> {
+    let upsert_settings = sqlx::query(indoc! {"
+        INSERT INTO guild_settings (guild_id, reactions_enabled)
+        VALUES ($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE
+        SET reactions_enabled = EXCLUDED.reactions_enabled;
+    "})
+    .bind(guild_id.get() as i64)
+    .bind(enabled);
+
+    update(db_pool, guild_id, upsert_settings).await?;
+    info!(enabled, "updated reactions setting");
+    Ok(())
+}
+
+pub async fn set_flagle_tiebreak_by_time(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    enabled: bool,
+) -> Result<(), SettingsError> {
+    let upsert_settings = sqlx::query(indoc! {"
+        INSERT INTO guild_settings (guild_id, flagle_tiebreak_by_time)
+        VALUES ($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE
+        SET flagle_tiebreak_by_time = EXCLUDED.flagle_tiebreak_by_time;
+    "})
+    .bind(guild_id.get() as i64)
+    .bind(enabled);
+
+    update(db_pool, guild_id, upsert_settings).await?;
+    info!(enabled, "updated Flagle tiebreak setting");
+    Ok(())
+}
+
+/// Set how all-time and windowed leaderboards are ranked. The medal points are kept as they were
+/// unless `ranking` is [`RankingStrategy::Points`].
+pub async fn set_ranking_strategy(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    ranking: RankingStrategy,
+) -> Result<(), SettingsError> {
+    let upsert_settings = match ranking {
+        RankingStrategy::Points {
+            gold,
+            silver,
+            bronze,
+        } => sqlx::query(indoc! {"
+            INSERT INTO guild_settings (
+                guild_id,
+                ranking_strategy,
+                gold_points,
+                silver_points,
+                bronze_points
+            )
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (guild_id) DO UPDATE
+            SET
+                ranking_strategy = EXCLUDED.ranking_strategy,
+                gold_points = EXCLUDED.gold_points,
+                silver_points = EXCLUDED.silver_points,
+                bronze_points = EXCLUDED.bronze_points;
+        "})
+        .bind(guild_id.get() as i64)
+        .bind(ranking.slug())
+        .bind(gold as i32)
+        .bind(silver as i32)
+        .bind(bronze as i32),
+        _ => sqlx::query(indoc! {"
+            INSERT INTO guild_settings (guild_id, ranking_strategy)
+            VALUES ($1, $2)
+            ON CONFLICT (guild_id) DO UPDATE
+            SET ranking_strategy = EXCLUDED.ranking_strategy;
+        "})
+        .bind(guild_id.get() as i64)
+        .bind(ranking.slug()),
+    };
+
+    update(db_pool, guild_id, upsert_settings).await?;
+    info!(%ranking, "updated ranking strategy");
+    Ok(())
+}
+
+pub async fn set_game_enabled(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    slug: &str,
+    enabled: bool,
+) -> Result<(), SettingsError> {
+    let query = if enabled {
+        sqlx::query(indoc! {"
+            DELETE FROM guild_disabled_games
+            WHERE guild_id = $1 AND game = $2;
+        "})
+    } else {
+        sqlx::query(indoc! {"
+            INSERT INTO guild_disabled_games (guild_id, game)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING;
+        "})
+    }
+    .bind(guild_id.get() as i64)
+    .bind(slug);
+
+    update(db_pool, guild_id, query).await?;
+    info!(game = %slug, enabled, "updated game setting");
+    Ok(())
+}
+
+/// Accept scores in `channel_id` (a channel or a category), either for the game with slug `game`
+/// or for every game if `game` is `None`.
+pub async fn add_score_channel(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    game: Option<&str>,
+) -> Result<(), SettingsError> {
+    let insert_channel = sqlx::query(indoc! {"
+        INSERT INTO guild_score_channels (guild_id, channel_id, game)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (guild_id, channel_id, COALESCE(game, '')) DO NOTHING;
+    "})
+    .bind(guild_id.get() as i64)
+    .bind(channel_id.get() as i64)
+    .bind(game);
+
+    update(db_pool, guild_id, insert_channel).await?;
+    info!(%channel_id, ?game, "added score channel");
+    Ok(())
+}
+
+pub async fn remove_score_channel(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    game: Option<&str>,
+) -> Result<(), SettingsError> {
+    let delete_channel = sqlx::query(indoc! {"
+        DELETE FROM guild_score_channels
+        WHERE guild_id = $1 AND channel_id = $2 AND game IS NOT DISTINCT FROM $3;
+    "})
+    .bind(guild_id.get() as i64)
+    .bind(channel_id.get() as i64)
+    .bind(game);
+
+    update(db_pool, guild_id, delete_channel).await?;
+    info!(%channel_id, ?game, "removed score channel");
+    Ok(())
+}
+
+/// Stop restricting the channels that scores are accepted in, either for the game with slug `game`
+/// or for every game if `game` is `None`.
+pub async fn clear_score_channels(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    game: Option<&str>,
+) -> Result<(), SettingsError> {
+    let delete_channels = sqlx::query(indoc! {"
+        DELETE FROM guild_score_channels
+        WHERE guild_id = $1 AND ($2::TEXT IS NULL OR game = $2);
+    "})
+    .bind(guild_id.get() as i64)
+    .bind(game);
+
+    update(db_pool, guild_id, delete_channels).await?;
+    info!(?game, "cleared score channels");
+    Ok(())
+}
+
+/// Run a query that changes a guild's settings, making sure the guild exists first.
+async fn update(
+    db_pool: &PgPool,
+    guild_id: GuildId,
+    query: Query<'_, Postgres, PgArguments>,
+) -> Result<(), SettingsError> {
+    let mut txn = db_pool.begin().await.map_err(SettingsError::BeginTxn)?;
+
+    insert_guild(&mut txn, guild_id).await?;
+
+    if let Err(error) = query.execute(txn.as_mut()).await {
+        error!(%error, "failed to update guild settings");
+        return Err(SettingsError::Unexpected(error));
+    }
+
+    txn.commit().await.map_err(SettingsError::CommitTxn)?;
+
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("failed to begin transaction: {0}")]
+    BeginTxn(#[source] SqlxError),
+
+    #[error("failed to commit transaction: {0}")]
+    CommitTxn(#[source] SqlxError),
+
+    #[error("failed to extract data from row: {0}")]
+    FromRow(#[source] SqlxError),
+
+    #[error("unexpected SQLx error: {0}")]
+    Unexpected(SqlxError),
+
+    #[error(transparent)]
+    GuildInsertion(#[from] GuildUserInsertionError),
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::ChannelId;
+
+    use super::{GuildSettings, RankingStrategy, ScoreChannel, SettingsQueryRow};
+
+    fn score_channel(channel_id: u64, game: Option<&str>) -> ScoreChannel {
+        ScoreChannel {
+            channel_id: ChannelId::new(channel_id),
+            game: game.map(ToOwned::to_owned),
+        }
+    }
+
+    #[test]
+    fn scores_accepted_everywhere_by_default() {
+        let settings = GuildSettings::default();
+
+        assert!(settings.accepts_scores_in("flagle", ChannelId::new(1), None));
+        assert!(settings.game_enabled("flagle"));
+        assert!(settings.reactions_enabled);
+    }
+
+    #[test]
+    fn scores_only_accepted_in_configured_channels() {
+        let settings = GuildSettings {
+            score_channels: vec![score_channel(1, None)],
+            ..GuildSettings::default()
+        };
+
+        assert!(settings.accepts_scores_in("flagle", ChannelId::new(1), None));
+        assert!(!settings.accepts_scores_in("flagle", ChannelId::new(2), None));
+    }
+
+    #[test]
+    fn scores_accepted_in_configured_categories() {
+        let settings = GuildSettings {
+            score_channels: vec![score_channel(10, None)],
+            ..GuildSettings::default()
+        };
+
+        assert!(settings.accepts_scores_in("flagle", ChannelId::new(2), Some(ChannelId::new(10))));
+        assert!(!settings.accepts_scores_in("flagle", ChannelId::new(2), Some(ChannelId::new(11))));
+    }
+
+    #[test]
+    fn score_channels_can_be_restricted_per_game() {
+        let settings = GuildSettings {
+            score_channels: vec![score_channel(1, Some("flagle")), score_channel(2, None)],
+            ..GuildSettings::default()
+        };
+
+        assert!(settings.accepts_scores_in("flagle", ChannelId::new(1), None));
+        assert!(settings.accepts_scores_in("flagle", ChannelId::new(2), None));
+        assert!(!settings.accepts_scores_in("wordle", ChannelId::new(1), None));
+        assert!(settings.accepts_scores_in("wordle", ChannelId::new(2), None));
+    }
+
+    #[test]
+    fn games_without_their_own_channels_are_unrestricted_if_no_channel_is_for_every_game() {
+        let settings = GuildSettings {
+            score_channels: vec![score_channel(1, Some("flagle"))],
+            ..GuildSettings::default()
+        };
+
+        assert!(!settings.accepts_scores_in("flagle", ChannelId::new(2), None));
+        assert!(settings.accepts_scores_in("wordle", ChannelId::new(2), None));
+    }
+
+    #[test]
+    fn disabled_games_are_not_enabled() {
+        let settings = GuildSettings {
+            disabled_games: vec!["wordle".to_owned()],
+            ..GuildSettings::default()
+        };
+
+        assert!(!settings.game_enabled("wordle"));
+        assert!(settings.game_enabled("flagle"));
+    }
+
+    #[test]
+    fn flagle_ties_are_shared_by_default() {
+        assert!(!GuildSettings::default().flagle_tiebreak_by_time);
+    }
+
+    #[test]
+    fn unknown_ranking_strategies_fall_back_to_medal_points() {
+        let mut row = SettingsQueryRow {
+            results_channel_id: None,
+            reactions_enabled: true,
+            disabled_games: Vec::new(),
+            score_channel_ids: Vec::new(),
+            score_channel_games: Vec::new(),
+            flagle_tiebreak_by_time: false,
+            ranking_strategy: "olympic".to_owned(),
+            gold_points: 4,
+            silver_points: 2,
+            bronze_points: 1,
+        };
+        assert_eq!(
+            GuildSettings::from(row.clone()).ranking,
+            RankingStrategy::Olympic
+        );
+
+        row.ranking_strategy = "nonsense".to_owned();
+        assert_eq!(GuildSettings::from(row).ranking, RankingStrategy::default());
+    }
+}